@@ -1,19 +1,65 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    coin, to_binary, Addr, BankMsg, Binary, Decimal, Decimal256, Deps, DepsMut, Empty, Env, GovMsg,
+    MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage, Uint128, Uint256,
+    VoteOption,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
+use cw721::{Cw721QueryMsg, OwnerOfResponse};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, one_coin};
+use semver::Version;
+use std::cmp::Reverse;
 
+use crate::conviction;
 use crate::error::ContractError;
+use crate::funding;
+use crate::merkle;
 use crate::msg::{
-    AllPollsResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, PollResponse, QueryMsg,
-    VoteResponse,
+    AllPollsResponse, ConfigResponse, ConvictionPoolResponse, ConvictionProposalResponse,
+    CreatePollMsg, DelegationsResponse, ElectionResultResponse, ExecuteMsg, FundingRoundResponse,
+    InstantiateMsg, LockResponse, MigrateMsg, PairwiseResponse, PollResponse, PollsByTagResponse,
+    ProjectFunding, QueryMsg, ResultsResponse, RewardResponse, TagsResponse, VoteMsg, VoteResponse,
 };
-use crate::state::{Ballot, Config, Poll, BALLOTS, CONFIG, POLLS};
+use crate::state::{
+    Ballot, Config, ConvictionPool, ConvictionProposal, FundingRound, Lock, Poll, PollStatus,
+    Reward, VotingMode, Weighting, ABSTAIN, BALLOTS, CONFIG, CONTRIBUTIONS, CONVICTION_POOL,
+    CONVICTION_PROPOSALS, CONVICTION_STAKES, DELEGATED_VOTES, DELEGATIONS, DELEGATORS,
+    ELIGIBLE_VOTERS, FUNDING_ROUNDS, LOCKS, LOCK_VOTES, NO, NONE_OF_THE_ABOVE, NO_WITH_VETO, POLLS,
+    POLL_TAGS, REWARDS, REWARD_CLAIMS, TAG_COUNTS, TOKEN_BALLOTS, YES,
+};
+use crate::tally::{self, RankedBallot};
 
 const CONTRACT_NAME: &str = "crates.io:cw-starter";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Oldest version whose stored polls and ballots this version can read
+const MIN_MIGRATE_VERSION: &str = "0.2.0";
+
+const MAX_OPTIONS: usize = 10;
+const MAX_TAGS: usize = 5;
+const MAX_TAG_LENGTH: usize = 32;
+
+// Largest credit budget, score or point budget a ballot can spread across options
+const MAX_BALLOT_POINTS: u64 = 1_000_000_000;
+
+// Most delegators a delegate can vote for, each ballot they cast checks and lists them all
+const MAX_DELEGATORS: usize = 50;
+
+// Most ballots, claims and other per poll entries one DeletePoll call removes
+const DELETE_PAGE: usize = 100;
+
+// Pagination defaults for list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// Longest a lock can run, a lock this long weighs its full amount
+const MAX_LOCK_DURATION: u64 = 4 * 365 * 24 * 60 * 60;
+
+// Time voters have to claim a poll's reward unless the creator sets it
+const DEFAULT_CLAIM_PERIOD: u64 = 7 * 24 * 60 * 60;
+const MAX_CLAIM_PERIOD: u64 = 365 * 24 * 60 * 60;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -36,6 +82,28 @@ pub fn instantiate(
         .add_attribute("admin", validated_admin.to_string()))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Only this contract can be migrated, and only forward from a compatible storage layout
+    let stored = get_contract_version(deps.storage)?;
+    let invalid = || ContractError::InvalidMigration {
+        contract: stored.contract.clone(),
+        version: stored.version.clone(),
+    };
+    let version = Version::parse(&stored.version).map_err(|_| invalid())?;
+    let current = Version::parse(CONTRACT_VERSION).map_err(|_| invalid())?;
+    let oldest = Version::parse(MIN_MIGRATE_VERSION).map_err(|_| invalid())?;
+    if stored.contract != CONTRACT_NAME || version < oldest || version > current {
+        return Err(invalid());
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -44,41 +112,200 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::CreatePoll {
+        ExecuteMsg::CreatePoll(msg) => execute_create_poll(deps, env, info, msg),
+        ExecuteMsg::Vote(msg) => execute_vote(deps, env, info, msg),
+        ExecuteMsg::UpdatePoll {
             poll_id,
             question,
             options,
-        } => execute_create_poll(deps, env, info, poll_id, question, options),
-        ExecuteMsg::Vote { poll_id, vote } => execute_vote(deps, env, info, poll_id, vote),
-        ExecuteMsg::DeletePoll { .. } => unimplemented!(),
-        ExecuteMsg::Revoke { .. } => unimplemented!(),
+        } => execute_update_poll(deps, env, info, poll_id, question, options),
+        ExecuteMsg::AddVoters { poll_id, voters } => {
+            execute_add_voters(deps, env, info, poll_id, voters)
+        }
+        ExecuteMsg::RemoveVoters { poll_id, voters } => {
+            execute_remove_voters(deps, env, info, poll_id, voters)
+        }
+        ExecuteMsg::Delegate { to } => execute_delegate(deps, env, info, to),
+        ExecuteMsg::Undelegate {} => execute_undelegate(deps, env, info),
+        ExecuteMsg::DeletePoll { poll_id } => execute_delete_poll(deps, env, info, poll_id),
+        ExecuteMsg::ClosePoll { poll_id } => execute_close_poll(deps, env, info, poll_id),
+        ExecuteMsg::ExecuteProposal { poll_id } => {
+            execute_execute_proposal(deps, env, info, poll_id)
+        }
+        ExecuteMsg::CastGovVote { poll_id } => execute_cast_gov_vote(deps, env, info, poll_id),
+        ExecuteMsg::RefreshWeight { poll_id } => execute_refresh_weight(deps, env, info, poll_id),
+        ExecuteMsg::Lock { duration } => execute_lock(deps, env, info, duration),
+        ExecuteMsg::Unlock {} => execute_unlock(deps, env, info),
+        ExecuteMsg::SetConvictionPool {
+            denom,
+            decay,
+            max_ratio,
+            weight,
+            min_threshold_stake,
+        } => execute_set_conviction_pool(
+            deps,
+            env,
+            info,
+            denom,
+            decay,
+            max_ratio,
+            weight,
+            min_threshold_stake,
+        ),
+        ExecuteMsg::FundConvictionPool {} => execute_fund_conviction_pool(deps, env, info),
+        ExecuteMsg::CreateConvictionProposal {
+            proposal_id,
+            title,
+            beneficiary,
+            requested,
+        } => execute_create_conviction_proposal(
+            deps,
+            env,
+            info,
+            proposal_id,
+            title,
+            beneficiary,
+            requested,
+        ),
+        ExecuteMsg::StakeConviction { proposal_id } => {
+            execute_stake_conviction(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::UnstakeConviction {
+            proposal_id,
+            amount,
+        } => execute_unstake_conviction(deps, env, info, proposal_id, amount),
+        ExecuteMsg::ExecuteConviction { proposal_id } => {
+            execute_execute_conviction(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::CreateFundingRound(msg) => execute_create_funding_round(deps, env, info, msg),
+        ExecuteMsg::RegisterProject { poll_id } => {
+            execute_register_project(deps, env, info, poll_id)
+        }
+        ExecuteMsg::Contribute { poll_id, project } => {
+            execute_contribute(deps, env, info, poll_id, project)
+        }
+        ExecuteMsg::Distribute { poll_id } => execute_distribute(deps, env, info, poll_id),
+        ExecuteMsg::ClaimReward { poll_id } => execute_claim_reward(deps, env, info, poll_id),
+        ExecuteMsg::ReclaimReward { poll_id } => execute_reclaim_reward(deps, env, info, poll_id),
+        ExecuteMsg::Revoke { sender, poll_id } => execute_revoke(deps, env, info, sender, poll_id),
     }
 }
 
 pub fn execute_create_poll(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    poll_id: String,
-    question: String,
-    options: Vec<String>,
+    msg: CreatePollMsg,
 ) -> Result<Response, ContractError> {
-    if options.len() > 10 {
-        return Err(ContractError::TooManyOptions {});
+    let CreatePollMsg {
+        poll_id,
+        question,
+        options,
+        tags,
+        eligible_voters,
+        allow_vote_change,
+        allow_abstain,
+        allow_none_of_the_above,
+        mode,
+        gov_proposal_id,
+        weighting,
+        min_balance,
+        min_cw20_balance,
+        reward_by_weight,
+        reward_claim_period,
+    } = msg;
+
+    let proposal = matches!(mode, VotingMode::Proposal { .. });
+    if (proposal && allow_none_of_the_above) || (!proposal && gov_proposal_id.is_some()) {
+        return Err(ContractError::InvalidVotingMode {});
+    }
+    let abstain = (allow_abstain || proposal).then_some(Uint128::zero());
+    let none_of_the_above = allow_none_of_the_above.then_some(Uint128::zero());
+    let opts = validate_options(mode_options(&mode, options)?, abstain, none_of_the_above)?;
+    validate_tags(&tags)?;
+    validate_mode(&mode, opts.len())?;
+    let weighting = match weighting {
+        Weighting::Merkle { merkle_root } => {
+            merkle::validate_root(&merkle_root)?;
+            Weighting::Merkle { merkle_root }
+        }
+        Weighting::Cw721 { cw721_addr } => Weighting::Cw721 {
+            cw721_addr: deps.api.addr_validate(cw721_addr.as_str())?,
+        },
+        weighting => weighting,
+    };
+    if POLLS.has(deps.storage, poll_id.clone()) {
+        return Err(ContractError::PollAlreadyExists {});
+    }
+    // Funds sent along are the reward for voting
+    let reward = if info.funds.is_empty() {
+        None
+    } else {
+        Some(one_coin(&info)?)
+    };
+    // Rewards are shared out to voters, token ballots belong to no voter
+    if reward.is_some() && matches!(weighting, Weighting::Cw721 { .. }) {
+        return Err(ContractError::TokenPollReward {});
+    }
+    let claim_period = reward_claim_period.unwrap_or(DEFAULT_CLAIM_PERIOD);
+    if claim_period == 0 || claim_period > MAX_CLAIM_PERIOD {
+        return Err(ContractError::InvalidClaimPeriod {});
+    }
+
+    for tag in &tags {
+        POLL_TAGS.save(deps.storage, (tag.clone(), poll_id.clone()), &Empty {})?;
+        TAG_COUNTS.update(deps.storage, tag.clone(), |count| -> StdResult<u64> {
+            Ok(count.unwrap_or_default() + 1)
+        })?;
     }
 
-    let mut opts: Vec<(String, u64)> = vec![];
-    for option in options {
-        opts.push((option, 0));
+    let restricted = eligible_voters.is_some();
+    for voter in eligible_voters.unwrap_or_default() {
+        let voter = deps.api.addr_validate(&voter)?;
+        ELIGIBLE_VOTERS.save(deps.storage, (poll_id.clone(), voter), &Empty {})?;
     }
 
+    let min_cw20_balance = match min_cw20_balance {
+        Some((addr, amount)) => Some((deps.api.addr_validate(&addr)?, amount)),
+        None => None,
+    };
+
+    let score_counts = score_counts(&mode, &opts);
+    let pairwise = pairwise(&mode, &opts);
     let poll = Poll {
         creator: info.sender,
         question,
         options: opts,
+        tags,
+        vote_count: 0,
+        restricted,
+        allow_vote_change: allow_vote_change.unwrap_or(true),
+        abstain,
+        none_of_the_above,
+        mode,
+        score_counts,
+        pairwise,
+        status: PollStatus::Open,
+        gov_proposal_id,
+        gov_vote_cast: false,
+        weighting,
+        min_balance,
+        min_cw20_balance,
+        start_time: env.block.time,
     };
 
     POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    if let Some(amount) = reward {
+        let reward = Reward {
+            amount,
+            by_weight: reward_by_weight,
+            claim_period,
+            total_shares: Uint128::zero(),
+            claim_deadline: None,
+            claimed: Uint128::zero(),
+        };
+        REWARDS.save(deps.storage, poll_id.clone(), &reward)?;
+    }
 
     Ok(Response::new()
         .add_attribute("action", "execute_create_poll")
@@ -86,505 +313,6151 @@ pub fn execute_create_poll(
 }
 
 fn execute_vote(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    poll_id: String,
-    vote: String,
+    msg: VoteMsg,
 ) -> Result<Response, ContractError> {
-    let poll = POLLS.may_load(deps.storage, poll_id.clone())?;
+    let VoteMsg {
+        poll_id,
+        vote,
+        proof,
+        weight,
+        allocation,
+        ranking,
+        token_ids,
+    } = msg;
+    // The poll does not exist so we just error
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
+    if FUNDING_ROUNDS.has(deps.storage, poll_id.clone()) {
+        return Err(ContractError::FundingRoundVote {});
+    }
 
-    match poll {
-        Some(mut poll) => {
-            // The poll exists
-            BALLOTS.update(
-                deps.storage,
-                (info.sender, poll_id.clone()),
-                |ballot| -> StdResult<Ballot> {
-                    match ballot {
-                        Some(ballot) => {
-                            // We need to revoke their old vote
-                            // Find the position
-                            let position_of_old_vote = poll
-                                .options
-                                .iter()
-                                .position(|option| option.0 == ballot.option)
-                                .unwrap();
-                            // Decrement by 1
-                            poll.options[position_of_old_vote].1 -= 1;
-                            // Update the ballot
-                            Ok(Ballot {
-                                option: vote.clone(),
-                            })
-                        }
-                        None => {
-                            // Simply add the ballot
-                            Ok(Ballot {
-                                option: vote.clone(),
-                            })
-                        }
-                    }
-                },
-            )?;
+    if poll.restricted && !ELIGIBLE_VOTERS.has(deps.storage, (poll_id.clone(), info.sender.clone()))
+    {
+        return Err(ContractError::NotEligible {});
+    }
+    check_min_balance(deps.as_ref(), &poll, &info.sender)?;
+    let weight = match &poll.weighting {
+        Weighting::Merkle { merkle_root } => {
+            merkle::verify(merkle_root, info.sender.as_str(), weight, &proof)?;
+            Uint128::from(weight.unwrap_or(1))
+        }
+        // A weight can only be claimed through a proof
+        _ if weight.is_some() => return Err(ContractError::InvalidProof {}),
+        Weighting::StakeWeighted => match bonded_stake(&deps.querier, &info.sender)? {
+            stake if stake.is_zero() => return Err(ContractError::NoStake {}),
+            stake => stake,
+        },
+        Weighting::Lock { .. } => match lock_weight(deps.storage, &poll, &info.sender)? {
+            weight if weight.is_zero() => return Err(ContractError::NoLock {}),
+            weight => weight,
+        },
+        Weighting::Equal | Weighting::Cw721 { .. } => Uint128::one(),
+    };
 
-            // Find the position of the new vote option and increment it by 1
-            let position = poll.options.iter().position(|option| option.0 == vote);
-            if position.is_none() {
-                return Err(ContractError::OptionNonExistent {});
+    validate_ballot(&poll, &vote, &allocation, &ranking)?;
+
+    if let Weighting::Cw721 { cw721_addr } = poll.weighting.clone() {
+        let ballot = Ballot {
+            option: vote.clone(),
+            weight,
+            delegators: vec![],
+            allocation,
+            ranking,
+        };
+        cast_token_ballots(
+            deps.branch(),
+            &mut poll,
+            &poll_id,
+            &info.sender,
+            &cw721_addr,
+            &token_ids,
+            &ballot,
+        )?;
+    } else {
+        let previous = BALLOTS.may_load(deps.storage, (poll_id.clone(), info.sender.clone()))?;
+        match &previous {
+            Some(_) if !poll.allow_vote_change => return Err(ContractError::AlreadyVoted {}),
+            Some(ballot) => {
+                // We need to revoke their old vote
+                remove_ballot(&mut poll, ballot);
+                release_delegators(deps.storage, &poll_id, ballot);
+            }
+            None => {
+                // First time voting on this poll
+                poll.vote_count += 1;
+                // Voting directly overrides the vote cast by the sender's delegate
+                take_back_delegated_vote(deps.storage, &mut poll, &poll_id, &info.sender)?;
             }
-            let position = position.unwrap();
-            poll.options[position].1 += 1;
+        }
 
-            // Save the update
-            POLLS.save(deps.storage, poll_id.clone(), &poll)?;
-            Ok(Response::new()
-                .add_attribute("action", "execute_vote")
-                .add_attribute("poll_id", poll_id)
-                .add_attribute("vote", vote))
+        let delegators = claim_delegators(deps.branch(), &poll, &poll_id, &info.sender)?;
+        let ballot = Ballot {
+            option: vote.clone(),
+            weight: weight
+                .checked_add(Uint128::from(delegators.len() as u128))
+                .map_err(|_| ContractError::TallyOverflow {})?,
+            delegators,
+            allocation,
+            ranking,
+        };
+        add_ballot(&mut poll, &ballot)?;
+        update_reward_shares(deps.storage, &poll_id, previous.as_ref(), Some(&ballot))?;
+        if matches!(poll.weighting, Weighting::Lock { .. }) {
+            LOCK_VOTES.save(
+                deps.storage,
+                (info.sender.clone(), poll_id.clone()),
+                &Empty {},
+            )?;
         }
-        None => Err(ContractError::PollNotFound {}), // The poll does not exist so we just error
+        BALLOTS.save(deps.storage, (poll_id.clone(), info.sender), &ballot)?;
     }
+
+    // Save the update
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    Ok(Response::new()
+        .add_attribute("action", "execute_vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("vote", vote))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::AllPolls {} => query_all_polls(deps, env),
-        QueryMsg::Poll { poll_id } => query_poll(deps, env, poll_id),
-        QueryMsg::Vote { address, poll_id } => query_vote(deps, env, address, poll_id),
-        QueryMsg::Config {} => query_config(deps, env),
+fn execute_delegate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
+    let delegate = deps.api.addr_validate(&to)?;
+    if delegate == info.sender {
+        return Err(ContractError::InvalidDelegate {});
     }
-}
 
-fn query_all_polls(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let polls = POLLS
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|p| Ok(p?.1))
-        .collect::<StdResult<Vec<_>>>()?;
+    if let Some(previous) = DELEGATIONS.may_load(deps.storage, info.sender.clone())? {
+        DELEGATORS.remove(deps.storage, (previous, info.sender.clone()));
+    }
+    let delegators = DELEGATORS
+        .prefix(delegate.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(MAX_DELEGATORS)
+        .count();
+    if delegators >= MAX_DELEGATORS {
+        return Err(ContractError::TooManyDelegators {});
+    }
+    DELEGATIONS.save(deps.storage, info.sender.clone(), &delegate)?;
+    DELEGATORS.save(deps.storage, (delegate.clone(), info.sender), &Empty {})?;
 
-    to_binary(&AllPollsResponse { polls })
+    Ok(Response::new()
+        .add_attribute("action", "execute_delegate")
+        .add_attribute("delegate", delegate))
 }
 
-fn query_poll(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
-    let poll = POLLS.may_load(deps.storage, poll_id)?;
+fn execute_undelegate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let delegate = DELEGATIONS
+        .may_load(deps.storage, info.sender.clone())?
+        .ok_or(ContractError::DelegationNotFound {})?;
 
-    to_binary(&PollResponse { poll })
+    DELEGATIONS.remove(deps.storage, info.sender.clone());
+    DELEGATORS.remove(deps.storage, (delegate.clone(), info.sender));
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_undelegate")
+        .add_attribute("delegate", delegate))
 }
 
-fn query_vote(deps: Deps, _env: Env, address: String, poll_id: String) -> StdResult<Binary> {
-    let validated_address = deps.api.addr_validate(&address).unwrap();
-    let vote = BALLOTS.may_load(deps.storage, (validated_address, poll_id))?;
+fn execute_update_poll(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    question: String,
+    options: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
 
-    to_binary(&VoteResponse { vote })
-}
+    if info.sender != poll.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    // Editing options would silently change what existing ballots mean
+    if poll.vote_count > 0 {
+        return Err(ContractError::PollHasBallots {});
+    }
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
 
-fn query_config(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let config = CONFIG.load(deps.storage)?;
+    if FUNDING_ROUNDS.has(deps.storage, poll_id.clone()) {
+        validate_projects(deps.as_ref(), &options)?;
+    }
+    let options = mode_options(&poll.mode, options)?;
+    poll.options = validate_options(options, poll.abstain, poll.none_of_the_above)?;
+    validate_mode(&poll.mode, poll.options.len())?;
+    poll.score_counts = score_counts(&poll.mode, &poll.options);
+    poll.pairwise = pairwise(&poll.mode, &poll.options);
+    poll.question = question;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
 
-    to_binary(&ConfigResponse {
-        admin: config.admin,
-    })
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_poll")
+        .add_attribute("poll_id", poll_id))
 }
 
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::{attr, from_binary, Addr};
-    // helper to construct an attribute e.g. ("action", "instantiate")
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    // mock functions to mock an environment, message info, dependencies
-    use crate::contract::{execute, instantiate, query};
-    use crate::ContractError;
-    // the contract instantiate function
-    use crate::msg::{
-        AllPollsResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, PollResponse, QueryMsg,
-        VoteResponse,
-    };
-    use crate::state::{Ballot, Poll};
+fn execute_add_voters(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    voters: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if info.sender != poll.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    if poll.status == PollStatus::Deleting {
+        return Err(ContractError::PollNotFound {});
+    }
 
-    // Two fake addresses we will use to mock_info
-    pub const ADDR1: &str = "addr1";
-    pub const ADDR2: &str = "addr2";
+    for voter in &voters {
+        let voter = deps.api.addr_validate(voter)?;
+        ELIGIBLE_VOTERS.save(deps.storage, (poll_id.clone(), voter), &Empty {})?;
+    }
+    if !poll.restricted {
+        poll.restricted = true;
+        POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    }
 
-    #[test]
-    fn test_instantiate() {
-        // Mock the dependencies, must be mutable so we can pass it as a mutable, empty vector means our contract has no balance
-        let mut deps = mock_dependencies();
-        // Mock the contract environment, contains the block info, contract address, etc.
-        let env = mock_env();
-        // Mock the message info, ADDR1 will be the sender, the empty vec means we sent no funds.
-        let info = mock_info(ADDR1, &[]);
+    Ok(Response::new()
+        .add_attribute("action", "execute_add_voters")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voters", voters.len().to_string()))
+}
 
-        // Create a message where we (the sender) will be an admin
-        // As no admin is provided, the sender ADDR1 will become the admin
-        let msg = InstantiateMsg { admin: None };
-        // Call instantiate, unwrap to assert success
-        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+fn execute_remove_voters(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    voters: Vec<String>,
+) -> Result<Response, ContractError> {
+    let poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if info.sender != poll.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    if poll.status == PollStatus::Deleting {
+        return Err(ContractError::PollNotFound {});
+    }
 
-        assert_eq!(
-            res.attributes,
-            vec![attr("action", "instantiate"), attr("admin", ADDR1)]
-        );
+    for voter in &voters {
+        let voter = deps.api.addr_validate(voter)?;
+        ELIGIBLE_VOTERS.remove(deps.storage, (poll_id.clone(), voter));
     }
 
-    #[test]
-    fn test_instantiate_with_admin() {
-        // Mock the dependencies, must be mutable so we can pass it as a mutable, empty vector means our contract has no balance
-        let mut deps = mock_dependencies();
-        // Mock the contract environment, contains the block info, contract address, etc.
-        let env = mock_env();
-        // Mock the message info, ADDR1 will be the sender, the empty vec means we sent no funds.
-        let info = mock_info(ADDR1, &[]);
+    Ok(Response::new()
+        .add_attribute("action", "execute_remove_voters")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voters", voters.len().to_string()))
+}
 
-        // Create a message where we (the sender) will be an admin
-        // As no admin is provided, the sender ADDR1 will become the admin
-        let msg = InstantiateMsg {
-            admin: Some(ADDR2.to_string()),
-        };
-        // Call instantiate, unwrap to assert success
-        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+fn execute_delete_poll(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
 
-        assert_eq!(
-            res.attributes,
-            vec![attr("action", "instantiate"), attr("admin", ADDR2)]
-        );
+    // Only the poll creator or the contract admin can delete a poll
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
     }
 
-    #[test]
-    fn test_execute_create_poll_valid() {
-        let mut deps = mock_dependencies();
-        let env = mock_env();
-        let info = mock_info(ADDR1, &[]);
-        // Instantiate the contract
-        let msg = InstantiateMsg { admin: None };
-        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    if poll.status != PollStatus::Deleting {
+        // The reward has to be paid out first
+        if REWARDS.has(deps.storage, poll_id.clone()) {
+            return Err(ContractError::RewardOutstanding {});
+        }
+        // Contributions to a funding round are held until they are distributed
+        if FUNDING_ROUNDS.has(deps.storage, poll_id.clone()) {
+            if poll.status != PollStatus::Executed {
+                return Err(ContractError::NotDistributed {});
+            }
+            FUNDING_ROUNDS.remove(deps.storage, poll_id.clone());
+        }
+        for tag in &poll.tags {
+            POLL_TAGS.remove(deps.storage, (tag.clone(), poll_id.clone()));
+            let count = TAG_COUNTS.load(deps.storage, tag.clone())? - 1;
+            if count == 0 {
+                TAG_COUNTS.remove(deps.storage, tag.clone());
+            } else {
+                TAG_COUNTS.save(deps.storage, tag.clone(), &count)?;
+            }
+        }
+        // The poll can no longer be voted on while its entries are removed
+        poll.status = PollStatus::Deleting;
+    }
 
-        // New execute msg
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id".to_string(),
-            question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
-        };
+    // Clear the ballots as well so a poll later created with the same id starts empty.
+    // Polls with many ballots take several calls, each removes up to DELETE_PAGE entries.
+    let mut remaining = DELETE_PAGE;
+    let claims = REWARD_CLAIMS
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= claims.len();
+    for voter in claims {
+        REWARD_CLAIMS.remove(deps.storage, (poll_id.clone(), voter));
+    }
+    let contributions = CONTRIBUTIONS
+        .sub_prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= contributions.len();
+    for (project, contributor) in contributions {
+        CONTRIBUTIONS.remove(deps.storage, (poll_id.clone(), project, contributor));
+    }
+    let voters = BALLOTS
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= voters.len();
+    for voter in voters {
+        BALLOTS.remove(deps.storage, (poll_id.clone(), voter));
+    }
+    let tokens = TOKEN_BALLOTS
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= tokens.len();
+    for token_id in tokens {
+        TOKEN_BALLOTS.remove(deps.storage, (poll_id.clone(), token_id));
+    }
+    let delegated = DELEGATED_VOTES
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= delegated.len();
+    for delegator in delegated {
+        DELEGATED_VOTES.remove(deps.storage, (poll_id.clone(), delegator));
+    }
+    let eligible = ELIGIBLE_VOTERS
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= eligible.len();
+    for voter in eligible {
+        ELIGIBLE_VOTERS.remove(deps.storage, (poll_id.clone(), voter));
+    }
 
-        // Unwrap to assert success
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // Every entry fit in this page, otherwise the poll is kept until the next call
+    let complete = remaining > 0;
+    if complete {
+        POLLS.remove(deps.storage, poll_id.clone());
+    } else {
+        POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    }
 
-        assert_eq!(
-            res.attributes,
-            vec![
-                attr("action", "execute_create_poll"),
-                attr("poll_id", "some_id")
-            ]
-        );
+    Ok(Response::new()
+        .add_attribute("action", "execute_delete_poll")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("complete", complete.to_string()))
+}
+
+fn execute_close_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
     }
 
-    #[test]
-    fn test_execute_create_poll_invalid() {
-        let mut deps = mock_dependencies();
-        let env = mock_env();
-        let info = mock_info(ADDR1, &[]);
-        // Instantiate the contract
-        let msg = InstantiateMsg { admin: None };
-        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    poll.status = closed_status(&poll);
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
 
-        let msg = ExecuteMsg::CreatePoll {
+    // Ballots can no longer change, so the reward shares are final
+    if let Some(mut reward) = REWARDS.may_load(deps.storage, poll_id.clone())? {
+        reward.claim_deadline = Some(env.block.time.plus_seconds(reward.claim_period));
+        REWARDS.save(deps.storage, poll_id.clone(), &reward)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_close_poll")
+        .add_attribute("poll_id", poll_id))
+}
+
+fn execute_execute_proposal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if poll.status != PollStatus::Passed {
+        return Err(ContractError::ProposalNotPassed {});
+    }
+
+    poll.status = PollStatus::Executed;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_execute_proposal")
+        .add_attribute("poll_id", poll_id))
+}
+
+fn execute_cast_gov_vote(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    let proposal_id = poll
+        .gov_proposal_id
+        .ok_or(ContractError::NoGovProposal {})?;
+    if poll.gov_vote_cast {
+        return Err(ContractError::GovVoteCast {});
+    }
+
+    let vote = match poll.status {
+        PollStatus::Passed | PollStatus::Executed => VoteOption::Yes,
+        PollStatus::Rejected => VoteOption::No,
+        PollStatus::Vetoed => VoteOption::NoWithVeto,
+        PollStatus::Open | PollStatus::Closed => return Err(ContractError::PollNotClosed {}),
+        PollStatus::Deleting => return Err(ContractError::PollNotFound {}),
+    };
+    if poll.status == PollStatus::Passed {
+        poll.status = PollStatus::Executed;
+    }
+    poll.gov_vote_cast = true;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_message(GovMsg::Vote { proposal_id, vote })
+        .add_attribute("action", "execute_cast_gov_vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+fn execute_refresh_weight(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if poll.weighting != Weighting::StakeWeighted {
+        return Err(ContractError::NotStakeWeighted {});
+    }
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
+    let key = (poll_id.clone(), info.sender.clone());
+    let mut ballot = BALLOTS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::BallotNotFound {})?;
+
+    // The old weight is taken out of the tally as it was counted
+    remove_ballot(&mut poll, &ballot);
+    let previous = ballot.clone();
+    ballot.weight = match bonded_stake(&deps.querier, &info.sender)? {
+        stake if stake.is_zero() => return Err(ContractError::NoStake {}),
+        stake => stake,
+    };
+    add_ballot(&mut poll, &ballot)?;
+    update_reward_shares(deps.storage, &poll_id, Some(&previous), Some(&ballot))?;
+    BALLOTS.save(deps.storage, key, &ballot)?;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_refresh_weight")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("weight", ballot.weight.to_string()))
+}
+
+fn execute_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let funds = one_coin(&info)?;
+    if duration == 0 || duration > MAX_LOCK_DURATION {
+        return Err(ContractError::InvalidLockDuration {});
+    }
+
+    let end = env.block.time.plus_seconds(duration);
+    let lock = match LOCKS.may_load(deps.storage, info.sender.clone())? {
+        Some(lock) if lock.amount.denom != funds.denom => {
+            return Err(ContractError::LockDenomMismatch {})
+        }
+        Some(lock) => Lock {
+            amount: coin((lock.amount.amount + funds.amount).u128(), funds.denom),
+            start: env.block.time,
+            end: lock.end.max(end),
+        },
+        None => Lock {
+            amount: funds,
+            start: env.block.time,
+            end,
+        },
+    };
+    LOCKS.save(deps.storage, info.sender.clone(), &lock)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_lock")
+        .add_attribute("amount", lock.amount.to_string())
+        .add_attribute("end", lock.end.seconds().to_string()))
+}
+
+fn execute_unlock(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let lock = LOCKS
+        .may_load(deps.storage, info.sender.clone())?
+        .ok_or(ContractError::NoLock {})?;
+    if env.block.time < lock.end {
+        return Err(ContractError::LockNotExpired {});
+    }
+
+    // Ballots on closed or deleted polls no longer hold the lock
+    let polls = LOCK_VOTES
+        .prefix(info.sender.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for poll_id in polls {
+        match POLLS.may_load(deps.storage, poll_id.clone())? {
+            Some(poll) if poll.status == PollStatus::Open => {
+                return Err(ContractError::LockInUse {})
+            }
+            _ => LOCK_VOTES.remove(deps.storage, (info.sender.clone(), poll_id)),
+        }
+    }
+    LOCKS.remove(deps.storage, info.sender.clone());
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![lock.amount.clone()],
+        })
+        .add_attribute("action", "execute_unlock")
+        .add_attribute("amount", lock.amount.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_set_conviction_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+    decay: Decimal,
+    max_ratio: Decimal,
+    weight: Decimal,
+    min_threshold_stake: Decimal,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if decay >= Decimal::one()
+        || max_ratio.is_zero()
+        || max_ratio > Decimal::one()
+        || weight.is_zero()
+        || min_threshold_stake.is_zero()
+        || min_threshold_stake > Decimal::one()
+    {
+        return Err(ContractError::InvalidConvictionPool {});
+    }
+
+    let pool = match CONVICTION_POOL.may_load(deps.storage)? {
+        Some(pool)
+            if pool.denom != denom && !(pool.funds.is_zero() && pool.total_staked.is_zero()) =>
+        {
+            return Err(ContractError::InvalidConvictionPool {})
+        }
+        Some(pool) => ConvictionPool {
+            denom,
+            decay,
+            max_ratio,
+            weight,
+            min_threshold_stake,
+            ..pool
+        },
+        None => ConvictionPool {
+            denom,
+            funds: Uint128::zero(),
+            total_staked: Uint128::zero(),
+            decay,
+            max_ratio,
+            weight,
+            min_threshold_stake,
+        },
+    };
+    CONVICTION_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new().add_attribute("action", "execute_set_conviction_pool"))
+}
+
+fn execute_fund_conviction_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut pool = CONVICTION_POOL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ConvictionPoolNotFound {})?;
+    let amount = must_pay(&info, &pool.denom)?;
+    pool.funds += amount;
+    CONVICTION_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_fund_conviction_pool")
+        .add_attribute("amount", amount))
+}
+
+fn execute_create_conviction_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: String,
+    title: String,
+    beneficiary: String,
+    requested: Uint128,
+) -> Result<Response, ContractError> {
+    if CONVICTION_POOL.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::ConvictionPoolNotFound {});
+    }
+    if requested.is_zero() {
+        return Err(ContractError::EmptyRequest {});
+    }
+    if CONVICTION_PROPOSALS.has(deps.storage, proposal_id.clone()) {
+        return Err(ContractError::ProposalAlreadyExists {});
+    }
+
+    let proposal = ConvictionProposal {
+        creator: info.sender,
+        title,
+        beneficiary: deps.api.addr_validate(&beneficiary)?,
+        requested,
+        staked: Uint128::zero(),
+        conviction: Uint128::zero(),
+        updated_at: env.block.height,
+        status: PollStatus::Open,
+    };
+    CONVICTION_PROPOSALS.save(deps.storage, proposal_id.clone(), &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_create_conviction_proposal")
+        .add_attribute("proposal_id", proposal_id))
+}
+
+fn execute_stake_conviction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: String,
+) -> Result<Response, ContractError> {
+    let mut pool = CONVICTION_POOL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ConvictionPoolNotFound {})?;
+    let mut proposal = CONVICTION_PROPOSALS
+        .may_load(deps.storage, proposal_id.clone())?
+        .ok_or(ContractError::ProposalNotFound {})?;
+    if proposal.status != PollStatus::Open {
+        return Err(ContractError::ProposalExecuted {});
+    }
+    let amount = must_pay(&info, &pool.denom)?;
+
+    // Conviction grew with the old stake until now
+    update_conviction(&mut proposal, &pool, env.block.height);
+    proposal.staked += amount;
+    pool.total_staked += amount;
+    CONVICTION_STAKES.update(
+        deps.storage,
+        (proposal_id.clone(), info.sender),
+        |staked| -> StdResult<_> { Ok(staked.unwrap_or_default() + amount) },
+    )?;
+    CONVICTION_PROPOSALS.save(deps.storage, proposal_id.clone(), &proposal)?;
+    CONVICTION_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_stake_conviction")
+        .add_attribute("proposal_id", proposal_id)
+        .add_attribute("amount", amount))
+}
+
+fn execute_unstake_conviction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut pool = CONVICTION_POOL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ConvictionPoolNotFound {})?;
+    let mut proposal = CONVICTION_PROPOSALS
+        .may_load(deps.storage, proposal_id.clone())?
+        .ok_or(ContractError::ProposalNotFound {})?;
+    let key = (proposal_id.clone(), info.sender.clone());
+    let staked = CONVICTION_STAKES
+        .may_load(deps.storage, key.clone())?
+        .unwrap_or_default();
+    if amount.is_zero() || amount > staked {
+        return Err(ContractError::InsufficientStake {});
+    }
+
+    update_conviction(&mut proposal, &pool, env.block.height);
+    proposal.staked -= amount;
+    // Stakes on executed proposals were already taken out of the total
+    if proposal.status == PollStatus::Open {
+        pool.total_staked -= amount;
+    }
+    if staked == amount {
+        CONVICTION_STAKES.remove(deps.storage, key);
+    } else {
+        CONVICTION_STAKES.save(deps.storage, key, &(staked - amount))?;
+    }
+    CONVICTION_PROPOSALS.save(deps.storage, proposal_id.clone(), &proposal)?;
+    CONVICTION_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![coin(amount.u128(), pool.denom)],
+        })
+        .add_attribute("action", "execute_unstake_conviction")
+        .add_attribute("proposal_id", proposal_id)
+        .add_attribute("amount", amount))
+}
+
+fn execute_execute_conviction(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    proposal_id: String,
+) -> Result<Response, ContractError> {
+    let mut pool = CONVICTION_POOL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ConvictionPoolNotFound {})?;
+    let mut proposal = CONVICTION_PROPOSALS
+        .may_load(deps.storage, proposal_id.clone())?
+        .ok_or(ContractError::ProposalNotFound {})?;
+    if proposal.status != PollStatus::Open {
+        return Err(ContractError::ProposalExecuted {});
+    }
+
+    update_conviction(&mut proposal, &pool, env.block.height);
+    let threshold = conviction::threshold(&pool, proposal.requested)
+        .ok_or(ContractError::RequestTooLarge {})?;
+    // A proposal nobody backs never passes
+    if proposal.staked.is_zero() || proposal.conviction < threshold {
+        return Err(ContractError::ConvictionBelowThreshold {});
+    }
+
+    pool.funds -= proposal.requested;
+    pool.total_staked -= proposal.staked;
+    proposal.status = PollStatus::Executed;
+    CONVICTION_PROPOSALS.save(deps.storage, proposal_id.clone(), &proposal)?;
+    CONVICTION_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: proposal.beneficiary.to_string(),
+            amount: vec![coin(proposal.requested.u128(), pool.denom)],
+        })
+        .add_attribute("action", "execute_execute_conviction")
+        .add_attribute("proposal_id", proposal_id)
+        .add_attribute("amount", proposal.requested))
+}
+
+// Grow the proposal's conviction up to `height`
+fn update_conviction(proposal: &mut ConvictionProposal, pool: &ConvictionPool, height: u64) {
+    let blocks = height.saturating_sub(proposal.updated_at);
+    proposal.conviction =
+        conviction::grow(proposal.conviction, proposal.staked, pool.decay, blocks);
+    proposal.updated_at = height;
+}
+
+fn execute_create_funding_round(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreatePollMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    // Contributions replace ballots, so the poll cannot weigh or add choices of its own
+    if msg.mode != VotingMode::SingleChoice
+        || msg.allow_abstain
+        || msg.allow_none_of_the_above
+        || msg.gov_proposal_id.is_some()
+        || msg.weighting != Weighting::Equal
+    {
+        return Err(ContractError::InvalidVotingMode {});
+    }
+    validate_projects(deps.as_ref(), &msg.options)?;
+    let matching_pool = one_coin(&info)?;
+
+    let poll_id = msg.poll_id.clone();
+    // The sent funds are the matching pool rather than a reward
+    let info = MessageInfo {
+        sender: info.sender,
+        funds: vec![],
+    };
+    execute_create_poll(deps.branch(), env, info, msg)?;
+    let round = FundingRound {
+        denom: matching_pool.denom,
+        matching_pool: matching_pool.amount,
+    };
+    FUNDING_ROUNDS.save(deps.storage, poll_id.clone(), &round)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_create_funding_round")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("matching_pool", round.matching_pool))
+}
+
+fn execute_register_project(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if !FUNDING_ROUNDS.has(deps.storage, poll_id.clone()) {
+        return Err(ContractError::NotFundingRound {});
+    }
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
+
+    let project = info.sender.to_string();
+    if poll.options.iter().any(|option| option.0 == project) {
+        return Err(ContractError::ProjectAlreadyRegistered {});
+    }
+    if poll.options.len() >= MAX_OPTIONS {
+        return Err(ContractError::TooManyOptions {});
+    }
+    poll.options.push((project.clone(), Uint128::zero()));
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_register_project")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("project", project))
+}
+
+fn execute_contribute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    project: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    let round = FUNDING_ROUNDS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::NotFundingRound {})?;
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
+    if poll.restricted && !ELIGIBLE_VOTERS.has(deps.storage, (poll_id.clone(), info.sender.clone()))
+    {
+        return Err(ContractError::NotEligible {});
+    }
+    check_min_balance(deps.as_ref(), &poll, &info.sender)?;
+    let amount = must_pay(&info, &round.denom)?;
+
+    // The option counts the project's contributors
+    let key = (poll_id.clone(), project.clone(), info.sender);
+    let contributed = CONTRIBUTIONS.may_load(deps.storage, key.clone())?;
+    if contributed.is_none() {
+        let count = tally_mut(&mut poll, &project).ok_or(ContractError::OptionNonExistent {})?;
+        *count += Uint128::one();
+        poll.vote_count += 1;
+        POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    }
+    CONTRIBUTIONS.save(
+        deps.storage,
+        key,
+        &(contributed.unwrap_or_default() + amount),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_contribute")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("project", project)
+        .add_attribute("amount", amount))
+}
+
+fn execute_distribute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    let round = FUNDING_ROUNDS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::NotFundingRound {})?;
+    match poll.status {
+        PollStatus::Open => return Err(ContractError::PollNotClosed {}),
+        PollStatus::Executed => return Err(ContractError::AlreadyDistributed {}),
+        _ => {}
+    }
+
+    let projects = project_funding(deps.storage, &poll_id, &poll, &round)?;
+    let mut messages = vec![];
+    let mut remaining = round.matching_pool;
+    for project in projects {
+        remaining -= project.matched;
+        let payout = project.contributed + project.matched;
+        if !payout.is_zero() {
+            messages.push(BankMsg::Send {
+                to_address: project.project,
+                amount: vec![coin(payout.u128(), &round.denom)],
+            });
+        }
+    }
+    if !remaining.is_zero() {
+        messages.push(BankMsg::Send {
+            to_address: poll.creator.to_string(),
+            amount: vec![coin(remaining.u128(), &round.denom)],
+        });
+    }
+    poll.status = PollStatus::Executed;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "execute_distribute")
+        .add_attribute("poll_id", poll_id))
+}
+
+// Contributions to every project of a funding round with their quadratic match
+fn project_funding(
+    storage: &dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    round: &FundingRound,
+) -> StdResult<Vec<ProjectFunding>> {
+    let contributions = poll
+        .options
+        .iter()
+        .map(|(project, _)| {
+            CONTRIBUTIONS
+                .prefix((poll_id.to_string(), project.clone()))
+                .range(storage, None, None, Order::Ascending)
+                .map(|item| Ok(item?.1))
+                .collect::<StdResult<Vec<_>>>()
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let matches = funding::matches(&contributions, round.matching_pool)?;
+
+    Ok(poll
+        .options
+        .iter()
+        .zip(contributions)
+        .zip(matches)
+        .map(|(((project, _), contributions), matched)| ProjectFunding {
+            project: project.clone(),
+            contributed: contributions.into_iter().sum(),
+            matched,
+        })
+        .collect())
+}
+
+// Funding round options are the addresses projects are paid to
+fn validate_projects(deps: Deps, projects: &[String]) -> StdResult<()> {
+    for project in projects {
+        deps.api.addr_validate(project)?;
+    }
+    Ok(())
+}
+
+fn execute_claim_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut reward = REWARDS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::NoReward {})?;
+    let deadline = reward
+        .claim_deadline
+        .ok_or(ContractError::PollNotClosed {})?;
+    if env.block.time >= deadline {
+        return Err(ContractError::ClaimPeriodOver {});
+    }
+    let key = (poll_id.clone(), info.sender.clone());
+    if REWARD_CLAIMS.has(deps.storage, key.clone()) {
+        return Err(ContractError::RewardClaimed {});
+    }
+    let ballot = BALLOTS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::BallotNotFound {})?;
+    // Every ballot weighs nothing, the creator can only reclaim the reward
+    if reward.total_shares.is_zero() {
+        return Err(ContractError::NoRewardShares {});
+    }
+
+    let shares = if reward.by_weight {
+        ballot.weight
+    } else {
+        Uint128::one()
+    };
+    let amount = reward
+        .amount
+        .amount
+        .multiply_ratio(shares, reward.total_shares);
+    reward.claimed += amount;
+    REWARD_CLAIMS.save(deps.storage, key, &Empty {})?;
+    REWARDS.save(deps.storage, poll_id.clone(), &reward)?;
+
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![coin(amount.u128(), &reward.amount.denom)],
+        });
+    }
+    Ok(response
+        .add_attribute("action", "execute_claim_reward")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("amount", amount))
+}
+
+fn execute_reclaim_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    let reward = REWARDS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::NoReward {})?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let deadline = reward
+        .claim_deadline
+        .ok_or(ContractError::PollNotClosed {})?;
+    if env.block.time < deadline {
+        return Err(ContractError::ClaimPeriodNotOver {});
+    }
+
+    let amount = reward.amount.amount - reward.claimed;
+    REWARDS.remove(deps.storage, poll_id.clone());
+
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        response = response.add_message(BankMsg::Send {
+            to_address: poll.creator.to_string(),
+            amount: vec![coin(amount.u128(), &reward.amount.denom)],
+        });
+    }
+    Ok(response
+        .add_attribute("action", "execute_reclaim_reward")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("amount", amount))
+}
+
+fn execute_revoke(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    // A voter can revoke their own ballot, the admin can revoke anyone's
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != sender && info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut poll = POLLS
+        .may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {})?;
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed {});
+    }
+    // Revoking and voting again would be a way around final ballots
+    if !poll.allow_vote_change && info.sender != config.admin {
+        return Err(ContractError::AlreadyVoted {});
+    }
+    let ballot = BALLOTS
+        .may_load(deps.storage, (poll_id.clone(), sender.clone()))?
+        .ok_or(ContractError::BallotNotFound {})?;
+
+    remove_ballot(&mut poll, &ballot);
+    release_delegators(deps.storage, &poll_id, &ballot);
+    update_reward_shares(deps.storage, &poll_id, Some(&ballot), None)?;
+    poll.vote_count -= 1;
+    BALLOTS.remove(deps.storage, (poll_id.clone(), sender.clone()));
+    LOCK_VOTES.remove(deps.storage, (sender.clone(), poll_id.clone()));
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_revoke")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("sender", sender))
+}
+
+// Voters must hold the poll's minimum native and cw20 balances
+fn check_min_balance(deps: Deps, poll: &Poll, voter: &Addr) -> Result<(), ContractError> {
+    if !meets_min_balance(deps, poll, voter)? {
+        return Err(ContractError::InsufficientBalance {});
+    }
+    Ok(())
+}
+
+fn meets_min_balance(deps: Deps, poll: &Poll, voter: &Addr) -> StdResult<bool> {
+    if let Some(min_balance) = &poll.min_balance {
+        let balance = deps.querier.query_balance(voter, &min_balance.denom)?;
+        if balance.amount < min_balance.amount {
+            return Ok(false);
+        }
+    }
+    if let Some((cw20_addr, amount)) = &poll.min_cw20_balance {
+        let balance: Cw20BalanceResponse = deps.querier.query_wasm_smart(
+            cw20_addr,
+            &Cw20QueryMsg::Balance {
+                address: voter.to_string(),
+            },
+        )?;
+        if balance.balance < *amount {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Cast `ballot` for every token of an NFT gated poll, the voter must own all of them.
+// A token that already voted changes its ballot, whoever cast it.
+fn cast_token_ballots(
+    deps: DepsMut,
+    poll: &mut Poll,
+    poll_id: &str,
+    voter: &Addr,
+    cw721_addr: &Addr,
+    token_ids: &[String],
+    ballot: &Ballot,
+) -> Result<(), ContractError> {
+    if token_ids.is_empty() {
+        return Err(ContractError::NotEligible {});
+    }
+    // Check every token before casting any ballot
+    let mut previous_ballots = vec![];
+    for (i, token_id) in token_ids.iter().enumerate() {
+        if token_ids[..i].contains(token_id) {
+            return Err(ContractError::DuplicateToken {});
+        }
+        let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+            cw721_addr,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.clone(),
+                include_expired: None,
+            },
+        )?;
+        if owner.owner != voter.as_str() {
+            return Err(ContractError::NotTokenOwner {});
+        }
+        let previous =
+            TOKEN_BALLOTS.may_load(deps.storage, (poll_id.to_string(), token_id.clone()))?;
+        if previous.is_some() && !poll.allow_vote_change {
+            return Err(ContractError::AlreadyVoted {});
+        }
+        previous_ballots.push(previous);
+    }
+
+    for (token_id, previous) in token_ids.iter().zip(previous_ballots) {
+        match previous {
+            Some(previous) => remove_ballot(poll, &previous),
+            None => poll.vote_count += 1,
+        }
+        let key = (poll_id.to_string(), token_id.clone());
+        add_ballot(poll, ballot)?;
+        TOKEN_BALLOTS.save(deps.storage, key, ballot)?;
+    }
+    Ok(())
+}
+
+// Count the delegators of `delegate` that have no say of their own in this poll yet.
+// Delegation is only available on Equal polls: delegators cannot be proven part of a merkle tree,
+// and stake or lock weighted ballots only weigh the voter's own stake or lock.
+// Delegators must hold the poll's minimum balances like any voter.
+fn claim_delegators(
+    deps: DepsMut,
+    poll: &Poll,
+    poll_id: &str,
+    delegate: &Addr,
+) -> StdResult<Vec<Addr>> {
+    if poll.weighting != Weighting::Equal {
+        return Ok(vec![]);
+    }
+
+    let candidates = DELEGATORS
+        .prefix(delegate.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut delegators = vec![];
+    for delegator in candidates {
+        let key = (poll_id.to_string(), delegator.clone());
+        if BALLOTS.has(deps.storage, key.clone())
+            || DELEGATED_VOTES.has(deps.storage, key.clone())
+            || (poll.restricted && !ELIGIBLE_VOTERS.has(deps.storage, key.clone()))
+            || !meets_min_balance(deps.as_ref(), poll, &delegator)?
+        {
+            continue;
+        }
+        DELEGATED_VOTES.save(deps.storage, key, delegate)?;
+        delegators.push(delegator);
+    }
+    Ok(delegators)
+}
+
+// Total stake an address has bonded to validators
+fn bonded_stake(querier: &QuerierWrapper, address: &Addr) -> StdResult<Uint128> {
+    querier
+        .query_all_delegations(address)?
+        .into_iter()
+        .try_fold(Uint128::zero(), |total, delegation| {
+            Ok(total.checked_add(delegation.amount.amount)?)
+        })
+}
+
+// Weight of the voter's lock: amount * lock time remaining at the poll start / MAX_LOCK_DURATION
+fn lock_weight(storage: &dyn Storage, poll: &Poll, voter: &Addr) -> StdResult<Uint128> {
+    let denom = match &poll.weighting {
+        Weighting::Lock { denom } => denom,
+        _ => return Ok(Uint128::zero()),
+    };
+    let lock = match LOCKS.may_load(storage, voter.clone())? {
+        Some(lock) if lock.amount.denom == *denom => lock,
+        _ => return Ok(Uint128::zero()),
+    };
+    // Locks created or topped up after the poll started would weigh more than they
+    // committed to at the start
+    if lock.start > poll.start_time {
+        return Ok(Uint128::zero());
+    }
+    let remaining = lock
+        .end
+        .seconds()
+        .saturating_sub(poll.start_time.seconds())
+        .min(MAX_LOCK_DURATION);
+    Ok(lock
+        .amount
+        .amount
+        .multiply_ratio(remaining, MAX_LOCK_DURATION))
+}
+
+// Keep the reward shares in step with the ballots, so closing the poll does not have to count them
+fn update_reward_shares(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    old: Option<&Ballot>,
+    new: Option<&Ballot>,
+) -> Result<(), ContractError> {
+    let mut reward = match REWARDS.may_load(storage, poll_id.to_string())? {
+        Some(reward) => reward,
+        None => return Ok(()),
+    };
+    let shares = |ballot: &Ballot| {
+        if reward.by_weight {
+            ballot.weight
+        } else {
+            Uint128::one()
+        }
+    };
+    let mut total = reward.total_shares;
+    if let Some(old) = old {
+        total = total.checked_sub(shares(old)).map_err(StdError::from)?;
+    }
+    if let Some(new) = new {
+        total = total
+            .checked_add(shares(new))
+            .map_err(|_| ContractError::TallyOverflow {})?;
+    }
+    reward.total_shares = total;
+    REWARDS.save(storage, poll_id.to_string(), &reward)?;
+    Ok(())
+}
+
+// The delegators of a removed ballot are free to be counted by another ballot
+fn release_delegators(storage: &mut dyn Storage, poll_id: &str, ballot: &Ballot) {
+    for delegator in &ballot.delegators {
+        DELEGATED_VOTES.remove(storage, (poll_id.to_string(), delegator.clone()));
+    }
+}
+
+// Remove `voter` from the ballot of the delegate that voted on their behalf, if any
+fn take_back_delegated_vote(
+    storage: &mut dyn Storage,
+    poll: &mut Poll,
+    poll_id: &str,
+    voter: &Addr,
+) -> Result<(), ContractError> {
+    let key = (poll_id.to_string(), voter.clone());
+    if let Some(delegate) = DELEGATED_VOTES.may_load(storage, key.clone())? {
+        let delegate_key = (poll_id.to_string(), delegate);
+        let mut ballot = BALLOTS.load(storage, delegate_key.clone())?;
+        remove_ballot(poll, &ballot);
+        let previous = ballot.clone();
+        ballot.delegators.retain(|delegator| delegator != voter);
+        ballot.weight -= Uint128::one();
+        add_ballot(poll, &ballot)?;
+        update_reward_shares(storage, poll_id, Some(&previous), Some(&ballot))?;
+        BALLOTS.save(storage, delegate_key, &ballot)?;
+        DELEGATED_VOTES.remove(storage, key);
+    }
+    Ok(())
+}
+
+// Turn the option names into a zeroed tally, enabled built-in choices reserve their name
+fn validate_options(
+    options: Vec<String>,
+    abstain: Option<Uint128>,
+    none_of_the_above: Option<Uint128>,
+) -> Result<Vec<(String, Uint128)>, ContractError> {
+    if options.len() > MAX_OPTIONS {
+        return Err(ContractError::TooManyOptions {});
+    }
+    let reserved = |option: &String| {
+        (abstain.is_some() && option == ABSTAIN)
+            || (none_of_the_above.is_some() && option == NONE_OF_THE_ABOVE)
+    };
+    if options.iter().any(reserved) {
+        return Err(ContractError::ReservedOption {});
+    }
+
+    Ok(options
+        .into_iter()
+        .map(|option| (option, Uint128::zero()))
+        .collect())
+}
+
+// Proposal polls have fixed options, other polls use the creator's
+fn mode_options(mode: &VotingMode, options: Vec<String>) -> Result<Vec<String>, ContractError> {
+    match mode {
+        VotingMode::Proposal { .. } if !options.is_empty() => {
+            Err(ContractError::InvalidVotingMode {})
+        }
+        VotingMode::Proposal { .. } => Ok(vec![
+            YES.to_string(),
+            NO.to_string(),
+            NO_WITH_VETO.to_string(),
+        ]),
+        _ => Ok(options),
+    }
+}
+
+// Tags must be unique, non-blank and short
+fn validate_tags(tags: &[String]) -> Result<(), ContractError> {
+    if tags.len() > MAX_TAGS {
+        return Err(ContractError::TooManyTags {});
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        if tag.trim().is_empty() || tag.len() > MAX_TAG_LENGTH || tags[..i].contains(tag) {
+            return Err(ContractError::InvalidTag {});
+        }
+    }
+    Ok(())
+}
+
+// Parameters of the voting mode must make sense for the number of options
+fn validate_mode(mode: &VotingMode, options: usize) -> Result<(), ContractError> {
+    let valid = match *mode {
+        VotingMode::SingleChoice | VotingMode::Condorcet | VotingMode::Borda => true,
+        VotingMode::Quadratic { credits_per_voter } => {
+            credits_per_voter > 0 && credits_per_voter <= MAX_BALLOT_POINTS
+        }
+        VotingMode::Score { min, max } => min < max && max <= MAX_BALLOT_POINTS,
+        VotingMode::Allocation { total_points } => {
+            total_points > 0 && total_points <= MAX_BALLOT_POINTS
+        }
+        VotingMode::Stv { seats } => seats > 0 && seats as usize <= options,
+        VotingMode::Proposal {
+            quorum,
+            threshold,
+            veto_threshold,
+            electorate,
+        } => {
+            !electorate.is_zero()
+                && quorum <= Decimal::one()
+                && threshold <= Decimal::one()
+                && veto_threshold <= Decimal::one()
+        }
+    };
+    if !valid {
+        return Err(ContractError::InvalidVotingMode {});
+    }
+    Ok(())
+}
+
+// Check the ballot is well formed for the poll's voting mode
+fn validate_ballot(
+    poll: &Poll,
+    vote: &str,
+    allocation: &[(String, u64)],
+    ranking: &[String],
+) -> Result<(), ContractError> {
+    // Enabled built-in choices are cast with `vote` alone on every mode
+    if is_built_in(poll, vote) && allocation.is_empty() && ranking.is_empty() {
+        return Ok(());
+    }
+    let ranked = matches!(
+        poll.mode,
+        VotingMode::Stv { .. } | VotingMode::Condorcet | VotingMode::Borda
+    );
+    if ranked && !allocation.is_empty() {
+        return Err(ContractError::InvalidAllocation {});
+    }
+    if !ranked && !ranking.is_empty() {
+        return Err(ContractError::InvalidRanking {});
+    }
+
+    match poll.mode {
+        VotingMode::SingleChoice | VotingMode::Proposal { .. } => {
+            if !allocation.is_empty() {
+                return Err(ContractError::InvalidAllocation {});
+            }
+            if !is_choice(poll, vote) {
+                return Err(ContractError::OptionNonExistent {});
+            }
+        }
+        VotingMode::Quadratic { credits_per_voter } => {
+            validate_allocation(poll, allocation)?;
+            if allocation.iter().any(|(_, votes)| *votes == 0) {
+                return Err(ContractError::InvalidAllocation {});
+            }
+            let cost = allocation
+                .iter()
+                .try_fold(0u64, |cost, (_, votes)| {
+                    votes.checked_mul(*votes).and_then(|c| cost.checked_add(c))
+                })
+                .ok_or(ContractError::InsufficientCredits {})?;
+            if cost > credits_per_voter {
+                return Err(ContractError::InsufficientCredits {});
+            }
+        }
+        VotingMode::Score { min, max } => {
+            validate_allocation(poll, allocation)?;
+            if allocation
+                .iter()
+                .any(|(_, score)| *score < min || *score > max)
+            {
+                return Err(ContractError::InvalidAllocation {});
+            }
+        }
+        VotingMode::Allocation { total_points } => {
+            validate_allocation(poll, allocation)?;
+            let spent = allocation
+                .iter()
+                .try_fold(0u64, |spent, (_, points)| spent.checked_add(*points));
+            if spent != Some(total_points) {
+                return Err(ContractError::AllocationBudgetMismatch {});
+            }
+        }
+        VotingMode::Stv { .. } | VotingMode::Condorcet | VotingMode::Borda => {
+            validate_ranking(poll, ranking)?
+        }
+    }
+    Ok(())
+}
+
+// A ranking names each of the poll's options at most once, built-in choices excluded
+fn validate_ranking(poll: &Poll, ranking: &[String]) -> Result<(), ContractError> {
+    if ranking.is_empty() {
+        return Err(ContractError::InvalidRanking {});
+    }
+    for (i, option) in ranking.iter().enumerate() {
+        if ranking[..i].contains(option) {
+            return Err(ContractError::InvalidRanking {});
+        }
+        if !poll.options.iter().any(|other| &other.0 == option) {
+            return Err(ContractError::OptionNonExistent {});
+        }
+    }
+    Ok(())
+}
+
+// An allocation names each of the poll's options at most once, built-in choices excluded
+fn validate_allocation(poll: &Poll, allocation: &[(String, u64)]) -> Result<(), ContractError> {
+    if allocation.is_empty() {
+        return Err(ContractError::InvalidAllocation {});
+    }
+    for (i, (option, _)) in allocation.iter().enumerate() {
+        if allocation[..i].iter().any(|(other, _)| other == option) {
+            return Err(ContractError::InvalidAllocation {});
+        }
+        if !poll.options.iter().any(|other| &other.0 == option) {
+            return Err(ContractError::OptionNonExistent {});
+        }
+    }
+    Ok(())
+}
+
+// Zeroed rating counters for Score polls
+fn score_counts(mode: &VotingMode, options: &[(String, Uint128)]) -> Vec<Uint128> {
+    match mode {
+        VotingMode::Score { .. } => vec![Uint128::zero(); options.len()],
+        _ => vec![],
+    }
+}
+
+// The status a poll closes into, proposals are decided on their tally
+fn closed_status(poll: &Poll) -> PollStatus {
+    let (quorum, threshold, veto_threshold, electorate) = match poll.mode {
+        VotingMode::Proposal {
+            quorum,
+            threshold,
+            veto_threshold,
+            electorate,
+        } => (quorum, threshold, veto_threshold, electorate),
+        _ => return PollStatus::Closed,
+    };
+
+    // Every tally fits in a Uint128 but their sum may not
+    let count = |vote: &str| {
+        poll.options
+            .iter()
+            .find(|option| option.0 == vote)
+            .map_or(Uint256::zero(), |option| option.1.into())
+    };
+    let (yes, no, veto) = (count(YES), count(NO), count(NO_WITH_VETO));
+    let turnout = yes + no + veto + Uint256::from(poll.abstain.unwrap_or_default());
+    let ratio =
+        |numerator: Uint256, denominator: Uint256| Decimal256::from_ratio(numerator, denominator);
+    if turnout.is_zero() || ratio(turnout, electorate.into()) < quorum.into() {
+        PollStatus::Rejected
+    } else if ratio(veto, turnout) > veto_threshold.into() {
+        PollStatus::Vetoed
+    } else if !yes.is_zero() && ratio(yes, yes + no + veto) > threshold.into() {
+        PollStatus::Passed
+    } else {
+        PollStatus::Rejected
+    }
+}
+
+// Zeroed pairwise preference matrix for Condorcet polls
+fn pairwise(mode: &VotingMode, options: &[(String, Uint128)]) -> Vec<Vec<Uint128>> {
+    match mode {
+        VotingMode::Condorcet => vec![vec![Uint128::zero(); options.len()]; options.len()],
+        _ => vec![],
+    }
+}
+
+// Every (vote value, amount) the ballot adds to the tally before weighting
+fn ballot_votes<'a>(mode: &VotingMode, ballot: &'a Ballot) -> Vec<(&'a str, u64)> {
+    if ballot.allocation.is_empty() && ballot.ranking.is_empty() {
+        vec![(ballot.option.as_str(), 1)]
+    } else if *mode == VotingMode::Borda {
+        let k = ballot.ranking.len() as u64;
+        ballot
+            .ranking
+            .iter()
+            .zip((0..k).rev())
+            .map(|(option, points)| (option.as_str(), points))
+            .collect()
+    } else if let Some(first) = ballot.ranking.first() {
+        // Ranked ballots count as a first preference
+        vec![(first.as_str(), 1)]
+    } else {
+        ballot
+            .allocation
+            .iter()
+            .map(|(option, amount)| (option.as_str(), *amount))
+            .collect()
+    }
+}
+
+// Whether a vote value is one of the poll's options or an enabled built-in choice
+fn is_choice(poll: &Poll, vote: &str) -> bool {
+    is_built_in(poll, vote) || poll.options.iter().any(|option| option.0 == vote)
+}
+
+// Whether a vote value is an enabled built-in choice
+fn is_built_in(poll: &Poll, vote: &str) -> bool {
+    (vote == ABSTAIN && poll.abstain.is_some())
+        || (vote == NONE_OF_THE_ABOVE && poll.none_of_the_above.is_some())
+}
+
+// The counter a vote value is tallied in, built-in choices are only valid when enabled
+fn tally_mut<'a>(poll: &'a mut Poll, vote: &str) -> Option<&'a mut Uint128> {
+    match vote {
+        ABSTAIN if poll.abstain.is_some() => poll.abstain.as_mut(),
+        NONE_OF_THE_ABOVE if poll.none_of_the_above.is_some() => poll.none_of_the_above.as_mut(),
+        _ => poll
+            .options
+            .iter_mut()
+            .find(|option| option.0 == vote)
+            .map(|option| &mut option.1),
+    }
+}
+
+// Count a ballot in the poll tally
+fn add_ballot(poll: &mut Poll, ballot: &Ballot) -> Result<(), ContractError> {
+    for (vote, amount) in ballot_votes(&poll.mode, ballot) {
+        let votes = Uint128::from(amount)
+            .checked_mul(ballot.weight)
+            .map_err(|_| ContractError::TallyOverflow {})?;
+        if let Some(count) = tally_mut(poll, vote) {
+            tally_add(count, votes)?;
+        }
+        if let Some(count) = score_count_mut(poll, vote) {
+            tally_add(count, ballot.weight)?;
+        }
+    }
+    for (winner, loser) in ballot_preferences(poll, ballot) {
+        tally_add(&mut poll.pairwise[winner][loser], ballot.weight)?;
+    }
+    Ok(())
+}
+
+// Add to a tally counter, failing rather than overflowing
+fn tally_add(count: &mut Uint128, amount: Uint128) -> Result<(), ContractError> {
+    *count = count
+        .checked_add(amount)
+        .map_err(|_| ContractError::TallyOverflow {})?;
+    Ok(())
+}
+
+// Take a previously cast ballot out of the poll tally, refunding quadratic credits
+fn remove_ballot(poll: &mut Poll, ballot: &Ballot) {
+    for (vote, amount) in ballot_votes(&poll.mode, ballot) {
+        if let Some(count) = tally_mut(poll, vote) {
+            *count -= Uint128::from(amount) * ballot.weight;
+        }
+        if let Some(count) = score_count_mut(poll, vote) {
+            *count -= ballot.weight;
+        }
+    }
+    for (winner, loser) in ballot_preferences(poll, ballot) {
+        poll.pairwise[winner][loser] -= ballot.weight;
+    }
+}
+
+// The rating counter of an option on Score polls
+fn score_count_mut<'a>(poll: &'a mut Poll, option: &str) -> Option<&'a mut Uint128> {
+    let position = poll.options.iter().position(|other| other.0 == option)?;
+    poll.score_counts.get_mut(position)
+}
+
+// Every (preferred, other) pair of option indexes a ranked ballot expresses on Condorcet polls.
+// Ranked options are preferred over every option left out of the ranking.
+fn ballot_preferences(poll: &Poll, ballot: &Ballot) -> Vec<(usize, usize)> {
+    if poll.pairwise.is_empty() {
+        return vec![];
+    }
+    let ranked: Vec<usize> = ballot
+        .ranking
+        .iter()
+        .filter_map(|option| poll.options.iter().position(|other| &other.0 == option))
+        .collect();
+    let mut preferences = vec![];
+    for (i, winner) in ranked.iter().enumerate() {
+        for loser in 0..poll.options.len() {
+            if !ranked[..=i].contains(&loser) {
+                preferences.push((*winner, loser));
+            }
+        }
+    }
+    preferences
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::AllPolls {} => query_all_polls(deps, env),
+        QueryMsg::Poll { poll_id } => query_poll(deps, env, poll_id),
+        QueryMsg::Vote { address, poll_id } => query_vote(deps, env, address, poll_id),
+        QueryMsg::TokenVote { poll_id, token_id } => query_token_vote(deps, env, poll_id, token_id),
+        QueryMsg::Results { poll_id } => query_results(deps, env, poll_id),
+        QueryMsg::ElectionResult { poll_id } => query_election_result(deps, env, poll_id),
+        QueryMsg::Pairwise { poll_id } => query_pairwise(deps, env, poll_id),
+        QueryMsg::Lock { address } => query_lock(deps, env, address),
+        QueryMsg::ConvictionPool {} => query_conviction_pool(deps, env),
+        QueryMsg::ConvictionProposal { proposal_id } => {
+            query_conviction_proposal(deps, env, proposal_id)
+        }
+        QueryMsg::FundingRound { poll_id } => query_funding_round(deps, env, poll_id),
+        QueryMsg::Reward { poll_id } => query_reward(deps, env, poll_id),
+        QueryMsg::Config {} => query_config(deps, env),
+        QueryMsg::PollsByTag {
+            tag,
+            start_after,
+            limit,
+        } => query_polls_by_tag(deps, env, tag, start_after, limit),
+        QueryMsg::Tags {} => query_tags(deps, env),
+        QueryMsg::Delegations { address } => query_delegations(deps, env, address),
+    }
+}
+
+fn query_all_polls(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let polls = POLLS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|p| Ok(p?.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&AllPollsResponse { polls })
+}
+
+fn query_poll(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.may_load(deps.storage, poll_id)?;
+
+    to_binary(&PollResponse { poll })
+}
+
+fn query_vote(deps: Deps, _env: Env, address: String, poll_id: String) -> StdResult<Binary> {
+    let validated_address = deps.api.addr_validate(&address).unwrap();
+    let vote = BALLOTS.may_load(deps.storage, (poll_id, validated_address))?;
+
+    to_binary(&VoteResponse { vote })
+}
+
+fn query_token_vote(deps: Deps, _env: Env, poll_id: String, token_id: String) -> StdResult<Binary> {
+    let vote = TOKEN_BALLOTS.may_load(deps.storage, (poll_id, token_id))?;
+
+    to_binary(&VoteResponse { vote })
+}
+
+fn query_results(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id)?;
+
+    let mut results: Vec<(String, Decimal256)> = match poll.mode {
+        VotingMode::Score { .. } => poll
+            .options
+            .into_iter()
+            .zip(poll.score_counts)
+            .map(|((option, sum), count)| {
+                let average = if count.is_zero() {
+                    Decimal256::zero()
+                } else {
+                    Decimal256::from_ratio(sum, count)
+                };
+                (option, average)
+            })
+            .collect(),
+        _ => poll
+            .options
+            .into_iter()
+            .map(|(option, count)| (option, Decimal256::from_ratio(count, 1u64)))
+            .collect(),
+    };
+    // Stable sort so ties keep the order the options were created in
+    results.sort_by_key(|result| Reverse(result.1));
+
+    to_binary(&ResultsResponse {
+        results,
+        abstain: poll.abstain,
+        none_of_the_above: poll.none_of_the_above,
+    })
+}
+
+fn query_election_result(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let seats = match poll.mode {
+        VotingMode::Stv { seats } => seats as usize,
+        _ => return Err(StdError::generic_err("Not an STV poll")),
+    };
+
+    let candidates: Vec<String> = poll.options.into_iter().map(|option| option.0).collect();
+    // Built-in choices are not ranked and do not count towards the quota
+    let ballots = BALLOTS
+        .prefix(poll_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((_, ballot)) if ballot.ranking.is_empty() => None,
+            Ok((_, ballot)) => {
+                let ranking = ballot
+                    .ranking
+                    .iter()
+                    .filter_map(|option| candidates.iter().position(|c| c == option))
+                    .collect();
+                Some(Ok(RankedBallot {
+                    ranking,
+                    weight: ballot.weight,
+                }))
+            }
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let result = tally::stv(&candidates, &ballots, seats);
+    to_binary(&ElectionResultResponse {
+        elected: result.elected,
+        rounds: result.rounds,
+    })
+}
+
+fn query_pairwise(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id)?;
+    if poll.mode != VotingMode::Condorcet {
+        return Err(StdError::generic_err("Not a Condorcet poll"));
+    }
+
+    let options: Vec<String> = poll.options.into_iter().map(|option| option.0).collect();
+    let condorcet_winner = tally::condorcet_winner(&poll.pairwise).map(|i| options[i].clone());
+    let winners = tally::schulze(&poll.pairwise)
+        .into_iter()
+        .map(|i| options[i].clone())
+        .collect();
+    to_binary(&PairwiseResponse {
+        options,
+        matrix: poll.pairwise,
+        condorcet_winner,
+        winners,
+    })
+}
+
+fn query_lock(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let lock = LOCKS.may_load(deps.storage, address)?;
+
+    to_binary(&LockResponse { lock })
+}
+
+fn query_conviction_pool(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let pool = CONVICTION_POOL.may_load(deps.storage)?;
+
+    to_binary(&ConvictionPoolResponse { pool })
+}
+
+fn query_conviction_proposal(deps: Deps, env: Env, proposal_id: String) -> StdResult<Binary> {
+    let pool = CONVICTION_POOL.may_load(deps.storage)?;
+    let mut proposal = CONVICTION_PROPOSALS.may_load(deps.storage, proposal_id)?;
+
+    let (conviction, threshold) = match (&pool, &mut proposal) {
+        (Some(pool), Some(proposal)) => {
+            update_conviction(proposal, pool, env.block.height);
+            (
+                proposal.conviction,
+                conviction::threshold(pool, proposal.requested),
+            )
+        }
+        _ => (Uint128::zero(), None),
+    };
+    to_binary(&ConvictionProposalResponse {
+        proposal,
+        conviction,
+        threshold,
+    })
+}
+
+fn query_funding_round(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let round = FUNDING_ROUNDS.may_load(deps.storage, poll_id.clone())?;
+    let projects = match (&round, POLLS.may_load(deps.storage, poll_id.clone())?) {
+        (Some(round), Some(poll)) => project_funding(deps.storage, &poll_id, &poll, round)?,
+        _ => vec![],
+    };
+
+    to_binary(&FundingRoundResponse { round, projects })
+}
+
+fn query_reward(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let reward = REWARDS.may_load(deps.storage, poll_id)?;
+
+    to_binary(&RewardResponse { reward })
+}
+
+fn query_config(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+
+    to_binary(&ConfigResponse {
+        admin: config.admin,
+    })
+}
+
+fn query_polls_by_tag(
+    deps: Deps,
+    _env: Env,
+    tag: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let polls = POLL_TAGS
+        .prefix(tag)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|poll_id| {
+            let poll_id = poll_id?;
+            let poll = POLLS.load(deps.storage, poll_id.clone())?;
+            Ok((poll_id, poll))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&PollsByTagResponse { polls })
+}
+
+fn query_tags(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let tags = TAG_COUNTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&TagsResponse { tags })
+}
+
+fn query_delegations(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegate = DELEGATIONS.may_load(deps.storage, address.clone())?;
+    let delegators = DELEGATORS
+        .prefix(address)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&DelegationsResponse {
+        delegate,
+        delegators,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        attr, coin, coins, from_binary, to_binary, Addr, BankMsg, ContractResult, Decimal,
+        Decimal256, Deps, Env, FullDelegation, GovMsg, OwnedDeps, SubMsg, SystemResult, Uint128,
+        VoteOption, WasmQuery,
+    };
+    use cw2::set_contract_version;
+    use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
+    use cw721::{Cw721QueryMsg, OwnerOfResponse};
+    use cw_utils::PaymentError;
+    // helper to construct an attribute e.g. ("action", "instantiate")
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    // mock functions to mock an environment, message info, dependencies
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::ContractError;
+    // the contract instantiate function
+    use crate::msg::{
+        AllPollsResponse, ConfigResponse, ConvictionPoolResponse, ConvictionProposalResponse,
+        CreatePollMsg, DelegationsResponse, ElectionResultResponse, ExecuteMsg,
+        FundingRoundResponse, InstantiateMsg, LockResponse, MigrateMsg, PairwiseResponse,
+        PollResponse, PollsByTagResponse, ProjectFunding, QueryMsg, ResultsResponse,
+        RewardResponse, TagsResponse, VoteMsg, VoteResponse,
+    };
+    use crate::state::{Ballot, Lock, Poll, PollStatus, VotingMode, Weighting};
+    use sha2::{Digest, Sha256};
+
+    // Two fake addresses we will use to mock_info
+    pub const ADDR1: &str = "addr1";
+    pub const ADDR2: &str = "addr2";
+
+    #[test]
+    fn test_instantiate() {
+        // Mock the dependencies, must be mutable so we can pass it as a mutable, empty vector means our contract has no balance
+        let mut deps = mock_dependencies();
+        // Mock the contract environment, contains the block info, contract address, etc.
+        let env = mock_env();
+        // Mock the message info, ADDR1 will be the sender, the empty vec means we sent no funds.
+        let info = mock_info(ADDR1, &[]);
+
+        // Create a message where we (the sender) will be an admin
+        // As no admin is provided, the sender ADDR1 will become the admin
+        let msg = InstantiateMsg { admin: None };
+        // Call instantiate, unwrap to assert success
+        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "instantiate"), attr("admin", ADDR1)]
+        );
+    }
+
+    #[test]
+    fn test_instantiate_with_admin() {
+        // Mock the dependencies, must be mutable so we can pass it as a mutable, empty vector means our contract has no balance
+        let mut deps = mock_dependencies();
+        // Mock the contract environment, contains the block info, contract address, etc.
+        let env = mock_env();
+        // Mock the message info, ADDR1 will be the sender, the empty vec means we sent no funds.
+        let info = mock_info(ADDR1, &[]);
+
+        // Create a message where we (the sender) will be an admin
+        // As no admin is provided, the sender ADDR1 will become the admin
+        let msg = InstantiateMsg {
+            admin: Some(ADDR2.to_string()),
+        };
+        // Call instantiate, unwrap to assert success
+        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "instantiate"), attr("admin", ADDR2)]
+        );
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "migrate"),
+                attr("from_version", env!("CARGO_PKG_VERSION")),
+                attr("to_version", env!("CARGO_PKG_VERSION"))
+            ]
+        );
+
+        // Other contracts, older storage layouts and downgrades are refused
+        for (contract, version) in [
+            ("crates.io:cw20-base", env!("CARGO_PKG_VERSION")),
+            ("crates.io:cw-starter", "0.1.0"),
+            ("crates.io:cw-starter", "99.0.0"),
+            ("crates.io:cw-starter", "latest"),
+        ] {
+            set_contract_version(deps.as_mut().storage, contract, version).unwrap();
+            let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidMigration {
+                    contract: contract.to_string(),
+                    version: version.to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_execute_create_poll_valid() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // New execute msg
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+
+        // Unwrap to assert success
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_create_poll"),
+                attr("poll_id", "some_id")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_create_poll_invalid() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite number?".to_string(),
+            options: vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "4".to_string(),
+                "5".to_string(),
+                "6".to_string(),
+                "7".to_string(),
+                "8".to_string(),
+                "9".to_string(),
+                "10".to_string(),
+                "11".to_string(),
+            ],
+            ..Default::default()
+        });
+
+        // Unwrap error to assert failure
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+
+        assert_eq!(err, ContractError::TooManyOptions {});
+    }
+
+    #[test]
+    fn test_execute_vote_valid() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create the poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create the vote, first time voting
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_vote"),
+                attr("poll_id", "some_id"),
+                attr("vote", "Juno")
+            ]
+        );
+
+        // Change the vote
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Osmosis".to_string(),
+            ..Default::default()
+        });
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_vote"),
+                attr("poll_id", "some_id"),
+                attr("vote", "Osmosis")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_invalid() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create the vote, some_id poll is not created yet.
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        // Unwrap to assert error
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::PollNotFound {});
+
+        // Create the poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Vote on a now existing poll but the option "DVPN" does not exist
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "DVPN".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
+    }
+
+    #[test]
+    fn test_execute_delete_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Deleting a poll that does not exist
+        let msg = ExecuteMsg::DeletePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::PollNotFound {});
+
+        // ADDR2 creates a poll and votes on it
+        let info2 = mock_info(ADDR2, &[]);
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap();
+
+        // A third party cannot delete it
+        let msg = ExecuteMsg::DeletePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // The admin (ADDR1) can delete it, the ballots go with it
+        let msg = ExecuteMsg::DeletePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_delete_poll"),
+                attr("poll_id", "some_id"),
+                attr("complete", "true")
+            ]
+        );
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(res.poll.is_none());
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id".to_string(),
+            address: ADDR2.to_string(),
+        };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert!(res.vote.is_none());
+    }
+
+    #[test]
+    fn test_execute_delete_poll_in_pages() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), create.clone()).unwrap();
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        for i in 0..150 {
+            let voter = format!("voter{}", i);
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(&voter, &[]),
+                vote.clone(),
+            )
+            .unwrap();
+        }
+
+        // The first call removes 100 ballots and keeps the poll from being voted on
+        let delete = ExecuteMsg::DeletePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), delete.clone()).unwrap();
+        assert_eq!(res.attributes[2], attr("complete", "false"));
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.poll.unwrap().status, PollStatus::Deleting);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PollClosed {});
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create.clone()).unwrap_err();
+        assert_eq!(err, ContractError::PollAlreadyExists {});
+
+        // The second call removes the rest along with the poll
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), delete).unwrap();
+        assert_eq!(res.attributes[2], attr("complete", "true"));
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(res.poll.is_none());
+
+        // A poll created with the same id starts empty
+        let _res = execute(deps.as_mut(), env.clone(), info, create).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("voter149", &[]), vote).unwrap();
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.vote_count, 1);
+    }
+
+    #[test]
+    fn test_execute_revoke() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create the poll, ADDR2 votes
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let info2 = mock_info(ADDR2, &[]);
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap();
+
+        // Someone else cannot revoke ADDR2's ballot
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // ADDR2 revokes their own ballot
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "some_id".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_revoke"),
+                attr("poll_id", "some_id"),
+                attr("sender", ADDR2)
+            ]
+        );
+
+        // The tally is back to zero
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Cosmos Hub".to_string(), Uint128::new(0)),
+                ("Juno".to_string(), Uint128::new(0))
+            ]
+        );
+
+        // Nothing left to revoke
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, info2, msg).unwrap_err();
+        assert_eq!(err, ContractError::BallotNotFound {});
+    }
+
+    #[test]
+    fn test_query_all_polls() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // No polls created, query and expect not response from AllPollsResponse
+        let msg = QueryMsg::AllPolls {};
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllPollsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.polls.len(), 0);
+
+        // Create a poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id_1".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create a second poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id_2".to_string(),
+            question: "What's your colour?".to_string(),
+            options: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Query
+        let msg = QueryMsg::AllPolls {};
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllPollsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.polls.len(), 2);
+        assert_eq!(
+            res.polls[0],
+            Poll {
+                creator: Addr::unchecked(ADDR1),
+                question: "What's your favourite Cosmos coin?".to_string(),
+                options: vec![
+                    ("Cosmos Hub".to_string(), Uint128::new(0)),
+                    ("Juno".to_string(), Uint128::new(0)),
+                    ("Osmosis".to_string(), Uint128::new(0))
+                ],
+                tags: vec![],
+                vote_count: 0,
+                restricted: false,
+                allow_vote_change: true,
+                abstain: None,
+                none_of_the_above: None,
+                mode: VotingMode::SingleChoice,
+                score_counts: vec![],
+                pairwise: vec![],
+                status: PollStatus::Open,
+                gov_proposal_id: None,
+                gov_vote_cast: false,
+                weighting: Weighting::Equal,
+                min_balance: None,
+                min_cw20_balance: None,
+                start_time: env.block.time,
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create a poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id_1".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Query for the poll that exists
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id_1".to_string(),
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        // Expect a poll
+        assert!(res.poll.is_some());
+        assert_eq!(
+            res,
+            PollResponse {
+                poll: Some(Poll {
+                    creator: Addr::unchecked(ADDR1),
+                    question: "What's your favourite Cosmos coin?".to_string(),
+                    options: vec![
+                        ("Cosmos Hub".to_string(), Uint128::new(0)),
+                        ("Juno".to_string(), Uint128::new(0)),
+                        ("Osmosis".to_string(), Uint128::new(0))
+                    ],
+                    tags: vec![],
+                    vote_count: 0,
+                    restricted: false,
+                    allow_vote_change: true,
+                    abstain: None,
+                    none_of_the_above: None,
+                    mode: VotingMode::SingleChoice,
+                    score_counts: vec![],
+                    pairwise: vec![],
+                    status: PollStatus::Open,
+                    gov_proposal_id: None,
+                    gov_vote_cast: false,
+                    weighting: Weighting::Equal,
+                    min_balance: None,
+                    min_cw20_balance: None,
+                    start_time: env.block.time,
+                })
+            }
+        );
+
+        // Query for the poll that does not exists
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id_not_exist".to_string(),
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        // Expect none
+        assert!(res.poll.is_none());
+    }
+
+    #[test]
+    fn test_query_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create a poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id_1".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Create a vote
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id_1".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Query for a vote that exists
+        // In this case we check if the ballot has been registered (voter_address, poll_id)
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id_1".to_string(),
+            address: ADDR1.to_string(),
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        // Expect the vote to exist
+        assert!(res.vote.is_some());
+        assert_eq!(
+            res,
+            VoteResponse {
+                vote: Some(Ballot {
+                    option: "Juno".to_string(),
+                    weight: Uint128::one(),
+                    delegators: vec![],
+                    allocation: vec![],
+                    ranking: vec![],
+                })
+            }
+        );
+
+        // Query for a vote that does not exists
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id_2".to_string(),
+            address: ADDR2.to_string(),
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        // Expect the vote to not exist
+        assert!(res.vote.is_none());
+    }
+
+    #[test]
+    fn test_query_config() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = QueryMsg::Config {};
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: ConfigResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.admin, Addr::unchecked(ADDR1));
+    }
+
+    #[test]
+    fn test_execute_create_poll_tags_invalid() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Six tags, the maximum is five
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            tags: vec!["a", "b", "c", "d", "e", "f"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyTags {});
+
+        // Blank, too long and duplicated tags are rejected
+        for tags in [
+            vec![" ".to_string()],
+            vec!["x".repeat(33)],
+            vec!["defi".to_string(), "defi".to_string()],
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "some_id".to_string(),
+                question: "What's your favourite Cosmos coin?".to_string(),
+                options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+                tags,
+                ..Default::default()
+            });
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidTag {});
+        }
+
+        // Poll ids cannot be reused while the poll exists
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PollAlreadyExists {});
+    }
+
+    #[test]
+    fn test_query_polls_by_tag() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Three polls, two of them tagged "defi"
+        for (poll_id, tags) in [
+            ("poll_1", vec!["defi", "juno"]),
+            ("poll_2", vec!["governance"]),
+            ("poll_3", vec!["defi"]),
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: poll_id.to_string(),
+                question: "What's your favourite Cosmos coin?".to_string(),
+                options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+                tags: tags.into_iter().map(String::from).collect(),
+                ..Default::default()
+            });
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let msg = QueryMsg::Tags {};
+        let res: TagsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.tags,
+            vec![
+                ("defi".to_string(), 2),
+                ("governance".to_string(), 1),
+                ("juno".to_string(), 1)
+            ]
+        );
+
+        // First page
+        let msg = QueryMsg::PollsByTag {
+            tag: "defi".to_string(),
+            start_after: None,
+            limit: Some(1),
+        };
+        let res: PollsByTagResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.polls.len(), 1);
+        assert_eq!(res.polls[0].0, "poll_1");
+
+        // Second page
+        let msg = QueryMsg::PollsByTag {
+            tag: "defi".to_string(),
+            start_after: Some("poll_1".to_string()),
+            limit: None,
+        };
+        let res: PollsByTagResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.polls.len(), 1);
+        assert_eq!(res.polls[0].0, "poll_3");
+        assert_eq!(res.polls[0].1.tags, vec!["defi".to_string()]);
+
+        // Deleting a poll removes it from the tag index
+        let msg = ExecuteMsg::DeletePoll {
+            poll_id: "poll_1".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::Tags {};
+        let res: TagsResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.tags,
+            vec![("defi".to_string(), 1), ("governance".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_execute_update_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Jnuo".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Only the creator can edit
+        let msg = ExecuteMsg::UpdatePoll {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Same validation as on creation
+        let too_many = ExecuteMsg::UpdatePoll {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite number?".to_string(),
+            options: (1..=11).map(|n| n.to_string()).collect(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), too_many).unwrap_err();
+        assert_eq!(err, ContractError::TooManyOptions {});
+
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_update_poll"),
+                attr("poll_id", "some_id")
+            ]
+        );
+        let query_msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.question, "What's your favourite Cosmos coin?");
+        assert_eq!(poll.options[1], ("Juno".to_string(), Uint128::new(0)));
+
+        // Once someone voted the poll is frozen
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), vote).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::PollHasBallots {});
+
+        // Revoking the only ballot unfreezes it
+        let revoke = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "some_id".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), revoke).unwrap();
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    }
+
+    #[test]
+    fn test_execute_vote_eligible_voters() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Only ADDR2 can vote on this poll
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "Should we raise the budget?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            eligible_voters: Some(vec![ADDR2.to_string()]),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Yes".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), vote.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NotEligible {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        // Only the creator manages the list
+        let add = ExecuteMsg::AddVoters {
+            poll_id: "some_id".to_string(),
+            voters: vec![ADDR1.to_string()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            add.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), add).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_add_voters"),
+                attr("poll_id", "some_id"),
+                attr("voters", "1")
+            ]
+        );
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote.clone()).unwrap();
+
+        // ADDR2 is removed and cannot vote anymore
+        let remove = ExecuteMsg::RemoveVoters {
+            poll_id: "some_id".to_string(),
+            voters: vec![ADDR2.to_string()],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, remove).unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), vote).unwrap_err();
+        assert_eq!(err, ContractError::NotEligible {});
+    }
+
+    // Build a merkle tree over `leaves` the same way the contract verifies it,
+    // returns the hex root and the hex proof of every leaf.
+    fn merkle_tree(leaves: &[&str]) -> (String, Vec<Vec<String>>) {
+        let mut level: Vec<[u8; 32]> = leaves
+            .iter()
+            .map(|leaf| Sha256::digest(leaf.as_bytes()).into())
+            .collect();
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut proofs: Vec<Vec<String>> = vec![vec![]; leaves.len()];
+
+        while level.len() > 1 {
+            for (leaf, position) in positions.iter_mut().enumerate() {
+                let sibling = *position ^ 1;
+                if sibling < level.len() {
+                    proofs[leaf].push(hex::encode(level[sibling]));
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => {
+                        let mut hashes = [*a, *b];
+                        hashes.sort_unstable();
+                        Sha256::digest(hashes.concat()).into()
+                    }
+                    // An odd node is promoted to the next level as is
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        (hex::encode(level[0]), proofs)
+    }
+
+    #[test]
+    fn test_execute_vote_merkle_proof() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // ADDR1 and ADDR2 are in the tree, ADDR2 with a weight of 5
+        let (root, proofs) = merkle_tree(&[ADDR1, "addr25", "addr3", "addr4", "addr5"]);
+
+        // The root must be a hex sha256 hash
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "Which airdrop should we run next?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            weighting: Weighting::Merkle {
+                merkle_root: "not hex".to_string(),
+            },
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMerkleRoot {});
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "Which airdrop should we run next?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            weighting: Weighting::Merkle { merkle_root: root },
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // ADDR1 votes with a plain leaf
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            proof: proofs[0].clone(),
+            weight: None,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // ADDR2 cannot claim a different weight than the one in its leaf
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            proof: proofs[1].clone(),
+            weight: Some(50),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidProof {});
+
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            proof: proofs[1].clone(),
+            weight: Some(5),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+
+        // Someone outside of the tree cannot reuse a proof
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Osmosis".to_string(),
+            proof: proofs[2].clone(),
+            weight: None,
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr6", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidProof {});
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Juno".to_string(), Uint128::new(6)),
+                ("Osmosis".to_string(), Uint128::new(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_weight_without_merkle_root() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // A weight can only come from a merkle proof
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            weight: Some(100),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidProof {});
+    }
+
+    #[test]
+    fn test_execute_vote_no_vote_change() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            allow_vote_change: Some(false),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // The setting is part of the poll response
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(!res.poll.unwrap().allow_vote_change);
+
+        let info2 = mock_info(ADDR2, &[]);
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap();
+
+        // Neither voting again nor revoking is allowed
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Cosmos Hub".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info2.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted {});
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info2, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted {});
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Cosmos Hub".to_string(), Uint128::new(0)),
+                ("Juno".to_string(), Uint128::new(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_delegation() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // addr3 and addr4 delegate to ADDR2
+        let msg = ExecuteMsg::Delegate {
+            to: ADDR2.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidDelegate {});
+        for delegator in ["addr3", "addr4"] {
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(delegator, &[]),
+                msg.clone(),
+            )
+            .unwrap();
+        }
+        let query_msg = QueryMsg::Delegations {
+            address: ADDR2.to_string(),
+        };
+        let res: DelegationsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            DelegationsResponse {
+                delegate: None,
+                delegators: vec![Addr::unchecked("addr3"), Addr::unchecked("addr4")],
+            }
+        );
+
+        // addr4 votes before its delegate so the delegate does not count it
+        let vote = |option: &str| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: option.to_string(),
+                ..Default::default()
+            })
+        };
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr4", &[]),
+            vote("Cosmos Hub"),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote("Juno"),
+        )
+        .unwrap();
+
+        let tally = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+            let msg = QueryMsg::Poll {
+                poll_id: "some_id".to_string(),
+            };
+            let res: PollResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.poll.unwrap().options
+        };
+        assert_eq!(
+            tally(&deps),
+            vec![
+                ("Cosmos Hub".to_string(), Uint128::new(1)),
+                ("Juno".to_string(), Uint128::new(2))
+            ]
+        );
+
+        // addr3 overrides its delegate
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            vote("Cosmos Hub"),
+        )
+        .unwrap();
+        assert_eq!(
+            tally(&deps),
+            vec![
+                ("Cosmos Hub".to_string(), Uint128::new(2)),
+                ("Juno".to_string(), Uint128::new(1))
+            ]
+        );
+        let query_msg = QueryMsg::Vote {
+            poll_id: "some_id".to_string(),
+            address: ADDR2.to_string(),
+        };
+        let res: VoteResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        let ballot = res.vote.unwrap();
+        assert_eq!(ballot.weight, Uint128::new(1));
+        assert!(ballot.delegators.is_empty());
+
+        // Undelegating only affects future ballots
+        let msg = ExecuteMsg::Undelegate {};
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env, mock_info("addr3", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::DelegationNotFound {});
+    }
+
+    #[test]
+    fn test_execute_delegate_limit() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // A delegate can vote for at most 50 delegators
+        let msg = ExecuteMsg::Delegate {
+            to: ADDR2.to_string(),
+        };
+        for i in 0..50 {
+            let delegator = format!("delegator{}", i);
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(&delegator, &[]),
+                msg.clone(),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyDelegators {});
+
+        // Delegating to the same delegate again does not take another place
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator0", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator0", &[]),
+            ExecuteMsg::Undelegate {},
+        )
+        .unwrap();
+        let _res = execute(deps.as_mut(), env, mock_info("addr3", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn test_execute_vote_builtin_choices() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Abstaining is not possible unless enabled
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "plain".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "plain".to_string(),
+            vote: "abstain".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
+
+        // Enabled built-in choices reserve their names
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "abstain".to_string()],
+            allow_abstain: true,
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::ReservedOption {});
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            allow_abstain: true,
+            allow_none_of_the_above: true,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for (voter, vote) in [
+            (ADDR1, "abstain"),
+            (ADDR2, "none_of_the_above"),
+            ("addr3", "Juno"),
+        ] {
+            let msg = ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: vote.to_string(),
+                ..Default::default()
+            });
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        // ADDR1 changes from abstain to Juno
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        // addr4 abstains
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "abstain".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("addr4", &[]), msg).unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                ("Cosmos Hub".to_string(), Uint128::new(0)),
+                ("Juno".to_string(), Uint128::new(2))
+            ]
+        );
+        assert_eq!(poll.abstain, Some(Uint128::new(1)));
+        assert_eq!(poll.none_of_the_above, Some(Uint128::new(1)));
+        assert_eq!(poll.vote_count, 4);
+
+        // The results report the built-in choices apart from the options
+        let msg = QueryMsg::Results {
+            poll_id: "some_id".to_string(),
+        };
+        let res: ResultsResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            ResultsResponse {
+                results: vec![
+                    ("Juno".to_string(), Decimal256::from_ratio(2u64, 1u64)),
+                    ("Cosmos Hub".to_string(), Decimal256::zero()),
+                ],
+                abstain: Some(Uint128::new(1)),
+                none_of_the_above: Some(Uint128::new(1)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_quadratic() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = |credits_per_voter| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "some_id".to_string(),
+                question: "Which grants should be funded?".to_string(),
+                options: vec![
+                    "Wallet".to_string(),
+                    "Explorer".to_string(),
+                    "Docs".to_string(),
+                ],
+                mode: VotingMode::Quadratic { credits_per_voter },
+                ..Default::default()
+            })
+        };
+        // Nobody could vote without credits
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create(0)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), create(100)).unwrap();
+
+        let vote = |allocation: Vec<(&str, u64)>| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                allocation: allocation
+                    .into_iter()
+                    .map(|(option, votes)| (option.to_string(), votes))
+                    .collect(),
+                ..Default::default()
+            })
+        };
+
+        // 8^2 + 6^2 = 100 credits is fine, 8^2 + 7^2 = 113 is not
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(vec![("Wallet", 8), ("Docs", 7)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientCredits {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(vec![("Wallet", 8), ("Docs", 6)]),
+        )
+        .unwrap();
+
+        // Malformed allocations
+        for allocation in [
+            vec![],
+            vec![("Wallet", 1), ("Wallet", 1)],
+            vec![("Wallet", 0)],
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(ADDR2, &[]),
+                vote(allocation),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidAllocation {});
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(vec![("Bridge", 1)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
+
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(vec![("Explorer", 10)]),
+        )
+        .unwrap();
+
+        // Re-voting refunds the previous allocation before spending again
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            vote(vec![("Explorer", 5), ("Docs", 5), ("Wallet", 5)]),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Wallet".to_string(), Uint128::new(5)),
+                ("Explorer".to_string(), Uint128::new(15)),
+                ("Docs".to_string(), Uint128::new(5))
+            ]
+        );
+
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id".to_string(),
+            address: ADDR1.to_string(),
+        };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.vote.unwrap().allocation.len(), 3);
+    }
+
+    #[test]
+    fn test_execute_vote_built_in_choices_on_other_modes() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let modes = [
+            (
+                "quadratic",
+                VotingMode::Quadratic {
+                    credits_per_voter: 100,
+                },
+            ),
+            ("score", VotingMode::Score { min: 0, max: 5 }),
+            ("borda", VotingMode::Borda),
+            ("stv", VotingMode::Stv { seats: 1 }),
+        ];
+        for (poll_id, mode) in modes {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: poll_id.to_string(),
+                question: "Which grants should be funded?".to_string(),
+                options: vec!["Wallet".to_string(), "Explorer".to_string()],
+                allow_abstain: true,
+                mode,
+                ..Default::default()
+            });
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            // Built-in choices need no allocation or ranking, but must be enabled
+            let vote = |vote: &str| {
+                ExecuteMsg::Vote(VoteMsg {
+                    poll_id: poll_id.to_string(),
+                    vote: vote.to_string(),
+                    ..Default::default()
+                })
+            };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                vote("none_of_the_above"),
+            )
+            .unwrap_err();
+            assert!(matches!(
+                err,
+                ContractError::InvalidAllocation {} | ContractError::InvalidRanking {}
+            ));
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("abstain")).unwrap();
+
+            let msg = QueryMsg::Poll {
+                poll_id: poll_id.to_string(),
+            };
+            let res: PollResponse =
+                from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            let poll = res.poll.unwrap();
+            assert_eq!(poll.abstain, Some(Uint128::new(1)));
+            assert_eq!(
+                poll.options,
+                vec![
+                    ("Wallet".to_string(), Uint128::new(0)),
+                    ("Explorer".to_string(), Uint128::new(0))
+                ]
+            );
+        }
+
+        // Abstentions do not count towards the STV quota
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "stv".to_string(),
+            ranking: vec!["Explorer".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        let msg = QueryMsg::ElectionResult {
+            poll_id: "stv".to_string(),
+        };
+        let res: ElectionResultResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.elected, vec!["Explorer".to_string()]);
+        assert_eq!(res.rounds.len(), 1);
+    }
+
+    #[test]
+    fn test_execute_vote_score() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = |mode| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "some_id".to_string(),
+                question: "Rate our products".to_string(),
+                options: vec![
+                    "Wallet".to_string(),
+                    "Explorer".to_string(),
+                    "Docs".to_string(),
+                ],
+                mode,
+                ..Default::default()
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create(VotingMode::Score { min: 5, max: 5 }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create(VotingMode::Score { min: 0, max: 5 }),
+        )
+        .unwrap();
+
+        let vote = |allocation: Vec<(&str, u64)>| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                allocation: allocation
+                    .into_iter()
+                    .map(|(option, score)| (option.to_string(), score))
+                    .collect(),
+                ..Default::default()
+            })
+        };
+
+        // Scores must be within the range
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(vec![("Wallet", 6)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidAllocation {});
+
+        // Explorer and Docs tie on average, Docs was not rated by addr3
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(vec![("Wallet", 5), ("Explorer", 2), ("Docs", 4)]),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(vec![("Wallet", 3), ("Explorer", 4), ("Docs", 2)]),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            vote(vec![("Wallet", 0), ("Explorer", 3)]),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                ("Wallet".to_string(), Uint128::new(8)),
+                ("Explorer".to_string(), Uint128::new(9)),
+                ("Docs".to_string(), Uint128::new(6))
+            ]
+        );
+        assert_eq!(
+            poll.score_counts,
+            vec![Uint128::new(3), Uint128::new(3), Uint128::new(2)]
+        );
+
+        let msg = QueryMsg::Results {
+            poll_id: "some_id".to_string(),
+        };
+        let res: ResultsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.results,
+            vec![
+                ("Explorer".to_string(), Decimal256::from_ratio(3u64, 1u64)),
+                ("Docs".to_string(), Decimal256::from_ratio(3u64, 1u64)),
+                ("Wallet".to_string(), Decimal256::from_ratio(8u64, 3u64)),
+            ]
+        );
+
+        // ADDR2 rates again, only Docs this time
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(vec![("Docs", 5)]),
+        )
+        .unwrap();
+        let msg = QueryMsg::Results {
+            poll_id: "some_id".to_string(),
+        };
+        let res: ResultsResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.results,
+            vec![
+                ("Docs".to_string(), Decimal256::from_ratio(9u64, 2u64)),
+                ("Wallet".to_string(), Decimal256::from_ratio(5u64, 2u64)),
+                ("Explorer".to_string(), Decimal256::from_ratio(5u64, 2u64)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_allocation() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "How should the treasury be spent?".to_string(),
+            options: vec![
+                "Marketing".to_string(),
+                "Development".to_string(),
+                "Security".to_string(),
+            ],
+            mode: VotingMode::Allocation { total_points: 100 },
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let vote = |allocation: Vec<(&str, u64)>| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                allocation: allocation
+                    .into_iter()
+                    .map(|(option, points)| (option.to_string(), points))
+                    .collect(),
+                ..Default::default()
+            })
+        };
+
+        // The whole budget has to be used, no more and no less
+        for allocation in [
+            vec![("Marketing", 50), ("Security", 40)],
+            vec![("Marketing", 60), ("Security", 41)],
+        ] {
+            let err =
+                execute(deps.as_mut(), env.clone(), info.clone(), vote(allocation)).unwrap_err();
+            assert_eq!(err, ContractError::AllocationBudgetMismatch {});
+        }
+
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(vec![
+                ("Marketing", 20),
+                ("Development", 50),
+                ("Security", 30),
+            ]),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(vec![("Security", 100)]),
+        )
+        .unwrap();
+        // ADDR1 moves everything to Development
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            vote(vec![("Development", 100)]),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Marketing".to_string(), Uint128::new(0)),
+                ("Development".to_string(), Uint128::new(100)),
+                ("Security".to_string(), Uint128::new(100))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_tally_overflow() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[
+                delegation(ADDR1, "validator1", 10u128.pow(30)),
+                delegation(ADDR2, "validator1", u128::MAX - 10u128.pow(30) + 1),
+            ],
+        );
+
+        let create = |poll_id: &str, mode| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: poll_id.to_string(),
+                question: "How should the treasury be spent?".to_string(),
+                options: vec!["Marketing".to_string(), "Development".to_string()],
+                mode,
+                weighting: Weighting::StakeWeighted,
+                ..Default::default()
+            })
+        };
+        // Budgets and scores are bounded
+        for mode in [
+            VotingMode::Allocation {
+                total_points: u64::MAX,
+            },
+            VotingMode::Score {
+                min: 0,
+                max: 1_000_000_001,
+            },
+            VotingMode::Quadratic {
+                credits_per_voter: 1_000_000_001,
+            },
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                create("poll_1", mode),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidVotingMode {});
+        }
+
+        // 1_000_000_000 points weighted by 10^30 do not fit in the tally
+        let mode = VotingMode::Allocation {
+            total_points: 1_000_000_000,
+        };
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("poll_1", mode),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "poll_1".to_string(),
+            allocation: vec![("Development".to_string(), 1_000_000_000)],
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::TallyOverflow {});
+
+        // Neither do the two stakes together
+        let mode = VotingMode::SingleChoice;
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("poll_2", mode),
+        )
+        .unwrap();
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "poll_2".to_string(),
+            vote: "Development".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, vote.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), vote).unwrap_err();
+        assert_eq!(err, ContractError::TallyOverflow {});
+
+        let msg = QueryMsg::Poll {
+            poll_id: "poll_2".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                ("Marketing".to_string(), Uint128::new(0)),
+                ("Development".to_string(), Uint128::new(10u128.pow(30)))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_election_result_stv() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = |seats| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "board".to_string(),
+                question: "Elect two board members".to_string(),
+                options: vec![
+                    "Alice".to_string(),
+                    "Bob".to_string(),
+                    "Carol".to_string(),
+                    "Dave".to_string(),
+                ],
+                mode: VotingMode::Stv { seats },
+                ..Default::default()
+            })
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create(5)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), create(2)).unwrap();
+
+        let vote = |ranking: &[&str]| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "board".to_string(),
+                ranking: ranking.iter().map(|option| option.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(&["Alice", "Alice"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidRanking {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            vote(&["Alice", "Eve"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
+
+        // 12 voters, the Droop quota for 2 seats is 12 / 3 + 1 = 5
+        let ballots: Vec<(usize, &[&str])> = vec![
+            (6, &["Alice", "Bob"]),
+            (2, &["Bob", "Carol"]),
+            (3, &["Carol", "Dave"]),
+            (1, &["Dave", "Carol"]),
+        ];
+        let mut voter = 0;
+        for (count, ranking) in ballots {
+            for _ in 0..count {
+                let sender = format!("voter{}", voter);
+                let _res = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(&sender, &[]),
+                    vote(ranking),
+                )
+                .unwrap();
+                voter += 1;
+            }
+        }
+
+        // The poll tally holds first preferences
+        let msg = QueryMsg::Poll {
+            poll_id: "board".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Alice".to_string(), Uint128::new(6)),
+                ("Bob".to_string(), Uint128::new(2)),
+                ("Carol".to_string(), Uint128::new(3)),
+                ("Dave".to_string(), Uint128::new(1))
+            ]
+        );
+
+        let msg = QueryMsg::ElectionResult {
+            poll_id: "board".to_string(),
+        };
+        let res: ElectionResultResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        // Alice reaches the quota and her surplus of 1 goes to Bob, Dave then Bob are
+        // eliminated which carries Carol over the line
+        assert_eq!(res.elected, vec!["Alice".to_string(), "Carol".to_string()]);
+        assert_eq!(res.rounds.len(), 4);
+        assert_eq!(
+            res.rounds[0].counts,
+            vec![
+                ("Alice".to_string(), Decimal256::from_ratio(6u64, 1u64)),
+                ("Bob".to_string(), Decimal256::from_ratio(2u64, 1u64)),
+                ("Carol".to_string(), Decimal256::from_ratio(3u64, 1u64)),
+                ("Dave".to_string(), Decimal256::from_ratio(1u64, 1u64)),
+            ]
+        );
+        assert_eq!(res.rounds[0].elected, vec!["Alice".to_string()]);
+        assert_eq!(res.rounds[1].eliminated, Some("Dave".to_string()));
+        assert_eq!(res.rounds[2].eliminated, Some("Bob".to_string()));
+        assert_eq!(res.rounds[3].elected, vec!["Carol".to_string()]);
+    }
+
+    #[test]
+    fn test_query_pairwise_condorcet() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "chair".to_string(),
+            question: "Who should chair the committee?".to_string(),
+            options: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+            mode: VotingMode::Condorcet,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |ranking: &[&str]| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "chair".to_string(),
+                ranking: ranking.iter().map(|option| option.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        let pairwise = |deps: Deps| -> PairwiseResponse {
+            let msg = QueryMsg::Pairwise {
+                poll_id: "chair".to_string(),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // A partial ranking prefers the ranked option over the unranked ones
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("voter0", &[]),
+            vote(&["B"]),
+        )
+        .unwrap();
+        let res = pairwise(deps.as_ref());
+        assert_eq!(
+            res.matrix,
+            vec![
+                vec![Uint128::new(0), Uint128::new(0), Uint128::new(0)],
+                vec![Uint128::new(1), Uint128::new(0), Uint128::new(1)],
+                vec![Uint128::new(0), Uint128::new(0), Uint128::new(0)]
+            ]
+        );
+        assert_eq!(res.condorcet_winner, Some("B".to_string()));
+        assert_eq!(res.winners, vec!["B".to_string()]);
+
+        // Changing the ballot takes its old preferences out of the matrix
+        let ballots: Vec<(usize, &[&str])> = vec![
+            (3, &["A", "B", "C"]),
+            (2, &["B", "C", "A"]),
+            (2, &["C", "A", "B"]),
+        ];
+        let mut voter = 0;
+        for (count, ranking) in ballots {
+            for _ in 0..count {
+                let sender = format!("voter{}", voter);
+                let _res = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(&sender, &[]),
+                    vote(ranking),
+                )
+                .unwrap();
+                voter += 1;
+            }
+        }
+
+        // A beats B 5-2, B beats C 5-2 and C beats A 4-3, a cycle without a Condorcet winner.
+        // A's beatpath to C through B is stronger than C beating A so Schulze picks A.
+        let res = pairwise(deps.as_ref());
+        assert_eq!(
+            res.options,
+            vec!["A".to_string(), "B".to_string(), "C".to_string()]
+        );
+        assert_eq!(
+            res.matrix,
+            vec![
+                vec![Uint128::new(0), Uint128::new(5), Uint128::new(3)],
+                vec![Uint128::new(2), Uint128::new(0), Uint128::new(5)],
+                vec![Uint128::new(4), Uint128::new(2), Uint128::new(0)]
+            ]
+        );
+        assert_eq!(res.condorcet_winner, None);
+        assert_eq!(res.winners, vec!["A".to_string()]);
+
+        // Only ranked ballots are accepted
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "chair".to_string(),
+            vote: "A".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env, mock_info("voter7", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRanking {});
+    }
+
+    #[test]
+    fn test_execute_vote_borda() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "venue".to_string(),
+            question: "Where should we meet?".to_string(),
+            options: vec![
+                "Berlin".to_string(),
+                "Lisbon".to_string(),
+                "Paris".to_string(),
+                "Rome".to_string(),
+            ],
+            mode: VotingMode::Borda,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |ranking: &[&str]| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "venue".to_string(),
+                ranking: ranking.iter().map(|option| option.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote(&["Rome", "Rome"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidRanking {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote(&["Oslo"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
+
+        // A ranking of 3 options awards 2, 1 and 0 points
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote(&["Paris", "Rome", "Berlin"]),
+        )
+        .unwrap();
+        // A ranking of every option awards 3, 2, 1 and 0 points
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote(&["Rome", "Lisbon", "Paris", "Berlin"]),
+        )
+        .unwrap();
+        // Changing a ballot takes back the points it awarded
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote(&["Lisbon", "Rome"]),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "venue".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.vote_count, 2);
+        assert_eq!(
+            poll.options,
+            vec![
+                ("Berlin".to_string(), Uint128::new(0)),
+                ("Lisbon".to_string(), Uint128::new(3)),
+                ("Paris".to_string(), Uint128::new(1)),
+                ("Rome".to_string(), Uint128::new(3))
+            ]
+        );
+
+        let msg = QueryMsg::Results {
+            poll_id: "venue".to_string(),
+        };
+        let res: ResultsResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.results,
+            vec![
+                ("Lisbon".to_string(), Decimal256::from_ratio(3u64, 1u64)),
+                ("Rome".to_string(), Decimal256::from_ratio(3u64, 1u64)),
+                ("Paris".to_string(), Decimal256::from_ratio(1u64, 1u64)),
+                ("Berlin".to_string(), Decimal256::zero()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_proposal_lifecycle() {
+        let proposal = |quorum: u64, electorate: u128| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "prop_1".to_string(),
+                question: "Upgrade the treasury contract?".to_string(),
+                mode: VotingMode::Proposal {
+                    quorum: Decimal::percent(quorum),
+                    threshold: Decimal::percent(50),
+                    veto_threshold: Decimal::permille(334),
+                    electorate: Uint128::new(electorate),
+                },
+                ..Default::default()
+            })
+        };
+        // Creates the proposal, casts one ballot per vote and closes it
+        let decide = |votes: &[&str]| -> PollStatus {
+            let mut deps = mock_dependencies();
+            let info = mock_info(ADDR1, &[]);
+            let msg = InstantiateMsg { admin: None };
+            let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+            let _res = execute(deps.as_mut(), mock_env(), info.clone(), proposal(40, 10)).unwrap();
+            for (i, vote) in votes.iter().enumerate() {
+                let msg = ExecuteMsg::Vote(VoteMsg {
+                    poll_id: "prop_1".to_string(),
+                    vote: vote.to_string(),
+                    ..Default::default()
+                });
+                let sender = format!("voter{}", i);
+                let _res =
+                    execute(deps.as_mut(), mock_env(), mock_info(&sender, &[]), msg).unwrap();
+            }
+            let msg = ExecuteMsg::ClosePoll {
+                poll_id: "prop_1".to_string(),
+            };
+            let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let msg = QueryMsg::Poll {
+                poll_id: "prop_1".to_string(),
+            };
+            let res: PollResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.poll.unwrap().status
+        };
+
+        // 5 of 10 voted, 3 yes of 4 non-abstaining votes
+        assert_eq!(
+            decide(&["yes", "yes", "yes", "no", "abstain"]),
+            PollStatus::Passed
+        );
+        // Abstentions make the quorum, but half of the other votes is not above the threshold
+        assert_eq!(
+            decide(&["yes", "no", "abstain", "abstain"]),
+            PollStatus::Rejected
+        );
+        // 3 of 10 voted, short of the 40% quorum
+        assert_eq!(decide(&["yes", "yes", "yes"]), PollStatus::Rejected);
+        // 2 of 5 vetoed, above the 33.4% veto threshold
+        assert_eq!(
+            decide(&["yes", "yes", "yes", "no_with_veto", "no_with_veto"]),
+            PollStatus::Vetoed
+        );
+        assert_eq!(decide(&[]), PollStatus::Rejected);
+
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Proposals have fixed options and valid parameters
+        let mut invalid = proposal(40, 10);
+        if let ExecuteMsg::CreatePoll(msg) = &mut invalid {
+            msg.options = vec!["maybe".to_string()];
+        }
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), invalid).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), proposal(101, 10)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), proposal(40, 0)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), proposal(10, 10)).unwrap();
+        let msg = QueryMsg::Poll {
+            poll_id: "prop_1".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                ("yes".to_string(), Uint128::new(0)),
+                ("no".to_string(), Uint128::new(0)),
+                ("no_with_veto".to_string(), Uint128::new(0))
+            ]
+        );
+        assert_eq!(poll.abstain, Some(Uint128::new(0)));
+        assert_eq!(poll.status, PollStatus::Open);
+
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "prop_1".to_string(),
+            vote: "yes".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        let close = ExecuteMsg::ClosePoll {
+            poll_id: "prop_1".to_string(),
+        };
+        let execute_proposal = ExecuteMsg::ExecuteProposal {
+            poll_id: "prop_1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            execute_proposal.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotPassed {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            close.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), close.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), close).unwrap_err();
+        assert_eq!(err, ContractError::PollClosed {});
+
+        // Ballots can no longer be cast or revoked
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), vote).unwrap_err();
+        assert_eq!(err, ContractError::PollClosed {});
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked(ADDR2),
+            poll_id: "prop_1".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PollClosed {});
+
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            execute_proposal.clone(),
+        )
+        .unwrap();
+        let msg = QueryMsg::Poll {
+            poll_id: "prop_1".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.poll.unwrap().status, PollStatus::Executed);
+        let err = execute(deps.as_mut(), env, info, execute_proposal).unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotPassed {});
+    }
+
+    #[test]
+    fn test_execute_proposal_large_turnout() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        // 10 tokens of an 18 decimals denom each
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[
+                delegation(ADDR1, "validator1", 10_000_000_000_000_000_000),
+                delegation(ADDR2, "validator1", 10_000_000_000_000_000_000),
+                delegation("addr3", "validator1", 5_000_000_000_000_000_000),
+            ],
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "prop_1".to_string(),
+            question: "Upgrade the treasury contract?".to_string(),
+            mode: VotingMode::Proposal {
+                quorum: Decimal::percent(40),
+                threshold: Decimal::percent(50),
+                veto_threshold: Decimal::permille(334),
+                electorate: Uint128::new(30_000_000_000_000_000_000),
+            },
+            weighting: Weighting::StakeWeighted,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for (voter, vote) in [(ADDR1, "yes"), (ADDR2, "abstain"), ("addr3", "no")] {
+            let msg = ExecuteMsg::Vote(VoteMsg {
+                poll_id: "prop_1".to_string(),
+                vote: vote.to_string(),
+                ..Default::default()
+            });
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        // The turnout of 2.5e19 does not fit in a u64
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "prop_1".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::Poll {
+            poll_id: "prop_1".to_string(),
+        };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.poll.unwrap().status, PollStatus::Passed);
+    }
+
+    #[test]
+    fn test_execute_cast_gov_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = |poll_id: &str, mode| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: poll_id.to_string(),
+                question: "How should the contract vote on proposal 7?".to_string(),
+                mode,
+                gov_proposal_id: Some(7),
+                ..Default::default()
+            })
+        };
+        let proposal = VotingMode::Proposal {
+            quorum: Decimal::percent(40),
+            threshold: Decimal::percent(50),
+            veto_threshold: Decimal::permille(334),
+            electorate: Uint128::new(2),
+        };
+        // Only proposal polls can mirror a chain proposal
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("poll_1", VotingMode::SingleChoice),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("poll_1", proposal),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "poll_1".to_string(),
+            vote: "no_with_veto".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+
+        let cast = ExecuteMsg::CastGovVote {
+            poll_id: "poll_1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            cast.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PollNotClosed {});
+
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "poll_1".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        // Anyone can relay the decision to the chain
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            cast.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(GovMsg::Vote {
+                proposal_id: 7,
+                vote: VoteOption::NoWithVeto,
+            })]
+        );
+        // The contract only votes once
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), cast).unwrap_err();
+        assert_eq!(err, ContractError::GovVoteCast {});
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_cast_gov_vote"),
+                attr("poll_id", "poll_1"),
+                attr("proposal_id", "7")
+            ]
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "poll_2".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::CastGovVote {
+            poll_id: "poll_2".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info(ADDR1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoGovProposal {});
+    }
+
+    fn delegation(delegator: &str, validator: &str, amount: u128) -> FullDelegation {
+        FullDelegation {
+            delegator: Addr::unchecked(delegator),
+            validator: validator.to_string(),
+            amount: coin(amount, "ustake"),
+            can_redelegate: coin(0, "ustake"),
+            accumulated_rewards: vec![],
+        }
+    }
+
+    #[test]
+    fn test_execute_vote_stake_weighted() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[
+                delegation(ADDR1, "validator1", 100),
+                delegation(ADDR1, "validator2", 50),
+                delegation(ADDR2, "validator1", 30),
+            ],
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
             poll_id: "some_id".to_string(),
-            question: "What's your favourite number?".to_string(),
-            options: vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "5".to_string(),
-                "6".to_string(),
-                "7".to_string(),
-                "8".to_string(),
-                "9".to_string(),
-                "10".to_string(),
-                "11".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            weighting: Weighting::StakeWeighted,
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |option: &str| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: option.to_string(),
+                ..Default::default()
+            })
+        };
+        let tally = |deps: Deps| {
+            let msg = QueryMsg::Poll {
+                poll_id: "some_id".to_string(),
+            };
+            let res: PollResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.poll.unwrap().options
+        };
+
+        // Ballots weigh the stake bonded across every validator
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno"),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote("Osmosis"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("nostake", &[]),
+            vote("Juno"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoStake {});
+        assert_eq!(
+            tally(deps.as_ref()),
+            vec![
+                ("Juno".to_string(), Uint128::new(150)),
+                ("Osmosis".to_string(), Uint128::new(30))
+            ]
+        );
+
+        // A changed vote takes back the weight it was cast with, not the current stake
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[
+                delegation(ADDR1, "validator1", 20),
+                delegation(ADDR2, "validator1", 80),
             ],
+        );
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Osmosis"),
+        )
+        .unwrap();
+        assert_eq!(
+            tally(deps.as_ref()),
+            vec![
+                ("Juno".to_string(), Uint128::new(0)),
+                ("Osmosis".to_string(), Uint128::new(50))
+            ]
+        );
+
+        let refresh = |poll_id: &str| ExecuteMsg::RefreshWeight {
+            poll_id: poll_id.to_string(),
         };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            refresh("some_id"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "execute_refresh_weight"),
+                attr("poll_id", "some_id"),
+                attr("weight", "80")
+            ]
+        );
+        assert_eq!(
+            tally(deps.as_ref()),
+            vec![
+                ("Juno".to_string(), Uint128::new(0)),
+                ("Osmosis".to_string(), Uint128::new(100))
+            ]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("nostake", &[]),
+            refresh("some_id"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BallotNotFound {});
 
-        // Unwrap error to assert failure
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        // A ballot cannot be refreshed to no stake
+        deps.querier.update_staking("ustake", &[], &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            refresh("some_id"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoStake {});
+        assert_eq!(
+            tally(deps.as_ref()),
+            vec![
+                ("Juno".to_string(), Uint128::new(0)),
+                ("Osmosis".to_string(), Uint128::new(100))
+            ]
+        );
 
-        assert_eq!(err, ContractError::TooManyOptions {});
+        // Stakes above u64::MAX, common on 18 decimal chains, are counted in full
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[delegation(ADDR2, "validator1", 20_000_000_000_000_000_000)],
+        );
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            refresh("some_id"),
+        )
+        .unwrap();
+        assert_eq!(
+            tally(deps.as_ref()),
+            vec![
+                ("Juno".to_string(), Uint128::new(0)),
+                (
+                    "Osmosis".to_string(),
+                    Uint128::new(20_000_000_000_000_000_020)
+                )
+            ]
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "other_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info(ADDR1, &[]),
+            refresh("other_id"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotStakeWeighted {});
+    }
+
+    // Answer cw721 OwnerOf queries with the given (token_id, owner)
+    fn mock_nft_owners(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        owners: &[(&str, &str)],
+    ) {
+        let owners: Vec<(String, String)> = owners
+            .iter()
+            .map(|(token_id, owner)| (token_id.to_string(), owner.to_string()))
+            .collect();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let token_id = match from_binary(msg).unwrap() {
+                    Cw721QueryMsg::OwnerOf { token_id, .. } => token_id,
+                    _ => panic!("unexpected cw721 query"),
+                };
+                let result = match owners.iter().find(|(id, _)| *id == token_id) {
+                    Some((_, owner)) => ContractResult::Ok(
+                        to_binary(&OwnerOfResponse {
+                            owner: owner.clone(),
+                            approvals: vec![],
+                        })
+                        .unwrap(),
+                    ),
+                    None => ContractResult::Err("token not found".to_string()),
+                };
+                SystemResult::Ok(result)
+            }
+            _ => panic!("unexpected wasm query"),
+        });
+    }
+
+    #[test]
+    fn test_execute_vote_nft_gated() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        mock_nft_owners(&mut deps, &[("1", ADDR1), ("2", ADDR1), ("3", ADDR2)]);
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            weighting: Weighting::Cw721 {
+                cw721_addr: Addr::unchecked("collection"),
+            },
+            ..Default::default()
+        });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(900, "ujuno")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TokenPollReward {});
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |option: &str, token_ids: &[&str]| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: option.to_string(),
+                token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno", &[]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotEligible {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno", &["1", "3"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotTokenOwner {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno", &["1", "1"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::DuplicateToken {});
+
+        // Each token is a vote
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno", &["1", "2"]),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote("Osmosis", &["3"]),
+        )
+        .unwrap();
+
+        // Token 1 changes hands, its new owner can only change the token's ballot
+        mock_nft_owners(&mut deps, &[("1", ADDR2), ("2", ADDR1), ("3", ADDR2)]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno", &["1"]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotTokenOwner {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote("Osmosis", &["1"]),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.vote_count, 3);
+        assert_eq!(
+            poll.options,
+            vec![
+                ("Juno".to_string(), Uint128::new(1)),
+                ("Osmosis".to_string(), Uint128::new(2))
+            ]
+        );
+
+        let msg = QueryMsg::TokenVote {
+            poll_id: "some_id".to_string(),
+            token_id: "1".to_string(),
+        };
+        let res: VoteResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.vote.unwrap().option, "Osmosis");
+        // Ballots are not kept per address on NFT gated polls
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id".to_string(),
+            address: ADDR2.to_string(),
+        };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.vote, None);
+    }
+
+    #[test]
+    fn test_execute_vote_min_balance() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        deps.querier.update_balance(ADDR1, coins(100, "ujuno"));
+        deps.querier.update_balance(ADDR2, coins(5, "ujuno"));
+        deps.querier.update_balance("whale", coins(1000, "ujuno"));
+        deps.querier.update_balance("addr3", coins(10, "ujuno"));
+        // cw20 balances of ADDR1, ADDR2 and addr3, everyone else holds none
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "token" => {
+                let balance = match from_binary(msg).unwrap() {
+                    Cw20QueryMsg::Balance { address } if address == ADDR1 => 60u128,
+                    Cw20QueryMsg::Balance { address } if address == ADDR2 => 100,
+                    Cw20QueryMsg::Balance { address } if address == "addr3" => 50,
+                    Cw20QueryMsg::Balance { .. } => 0,
+                    _ => panic!("unexpected cw20 query"),
+                };
+                let res = Cw20BalanceResponse {
+                    balance: Uint128::new(balance),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => panic!("unexpected wasm query"),
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            min_balance: Some(coin(10, "ujuno")),
+            min_cw20_balance: Some(("token".to_string(), Uint128::new(50))),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        // Not enough of the native denom
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientBalance {});
+        // Not enough of the cw20 token
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("whale", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientBalance {});
+
+        // Delegators only count when they hold the minimum balances themselves
+        for delegator in [ADDR2, "whale", "addr3"] {
+            let msg = ExecuteMsg::Delegate {
+                to: ADDR1.to_string(),
+            };
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(delegator, &[]), msg).unwrap();
+        }
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), vote).unwrap();
+
+        let msg = QueryMsg::Vote {
+            poll_id: "some_id".to_string(),
+            address: ADDR1.to_string(),
+        };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let ballot = res.vote.unwrap();
+        assert_eq!(ballot.delegators, vec![Addr::unchecked("addr3")]);
+        assert_eq!(ballot.weight, Uint128::new(2));
+    }
+
+    #[test]
+    fn test_execute_vote_lock_weighted() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instantiate the contract
+        let msg = InstantiateMsg { admin: None };
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        const YEAR: u64 = 365 * 24 * 60 * 60;
+        let lock = |duration| ExecuteMsg::Lock { duration };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            lock(YEAR),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(1000, "ujuno")),
+            lock(0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockDuration {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(1000, "ujuno")),
+            lock(5 * YEAR),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockDuration {});
+
+        // A maximum lock weighs its full amount, a one year lock a quarter of it
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(1000, "ujuno")),
+            lock(4 * YEAR),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(600, "ujuno")),
+            lock(YEAR),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(400, "uosmo")),
+            lock(YEAR),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::LockDenomMismatch {});
+        // Adding to a lock keeps its later end
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(400, "ujuno")),
+            lock(YEAR / 2),
+        )
+        .unwrap();
+        let msg = QueryMsg::Lock {
+            address: ADDR2.to_string(),
+        };
+        let res: LockResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.lock,
+            Some(Lock {
+                amount: coin(1000, "ujuno"),
+                start: env.block.time,
+                end: env.block.time.plus_seconds(YEAR),
+            })
+        );
+        // Locks above u64::MAX, common on 18 decimal chains, weigh in full
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr4", &coins(20_000_000_000_000_000_000, "ujuno")),
+            lock(4 * YEAR),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Juno".to_string(), "Osmosis".to_string()],
+            weighting: Weighting::Lock {
+                denom: "ujuno".to_string(),
+            },
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let vote = |option: &str| {
+            ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: option.to_string(),
+                ..Default::default()
+            })
+        };
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            vote("Juno"),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            vote("Osmosis"),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr4", &[]),
+            vote("Osmosis"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("nolock", &[]),
+            vote("Juno"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoLock {});
+        let msg = QueryMsg::Poll {
+            poll_id: "some_id".to_string(),
+        };
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options,
+            vec![
+                ("Juno".to_string(), Uint128::new(1000)),
+                (
+                    "Osmosis".to_string(),
+                    Uint128::new(20_000_000_000_000_000_250)
+                )
+            ]
+        );
+
+        // Locks made after the poll started do not count, a four year lock a year in would
+        // weigh more than its amount
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(YEAR);
+        let _res = execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("addr3", &coins(1000, "ujuno")),
+            lock(4 * YEAR),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("addr3", &[]),
+            vote("Juno"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoLock {});
+        // Neither do top ups after the poll started
+        let _res = execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info(ADDR1, &coins(1000, "ujuno")),
+            lock(YEAR),
+        )
+        .unwrap();
+        let err =
+            execute(deps.as_mut(), later, mock_info(ADDR1, &[]), vote("Osmosis")).unwrap_err();
+        assert_eq!(err, ContractError::NoLock {});
+
+        let unlock = ExecuteMsg::Unlock {};
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unlock.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::LockNotExpired {});
+        // The lock is held while the poll it voted on is open
+        env.block.time = env.block.time.plus_seconds(YEAR);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unlock.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::LockInUse {});
+
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unlock.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(1000, "ujuno"),
+            })]
+        );
+        let err = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), unlock).unwrap_err();
+        assert_eq!(err, ContractError::NoLock {});
     }
 
     #[test]
-    fn test_execute_vote_valid() {
+    fn test_execute_conviction_voting() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
+        let mut env = mock_env();
         let info = mock_info(ADDR1, &[]);
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
         let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        // Create the poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id".to_string(),
-            question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
+        let set_pool = |decay| ExecuteMsg::SetConvictionPool {
+            denom: "ujuno".to_string(),
+            decay,
+            max_ratio: Decimal::percent(20),
+            weight: Decimal::from_ratio(25u64, 10000u64),
+            min_threshold_stake: Decimal::percent(5),
         };
-        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            set_pool(Decimal::percent(90)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::one()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidConvictionPool {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::percent(90)),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::FundConvictionPool {};
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(10000, "ujuno")),
+            msg,
+        )
+        .unwrap();
 
-        // Create the vote, first time voting
-        let msg = ExecuteMsg::Vote {
-            poll_id: "some_id".to_string(),
-            vote: "Juno".to_string(),
+        let create = |proposal_id: &str, requested| ExecuteMsg::CreateConvictionProposal {
+            proposal_id: proposal_id.to_string(),
+            title: "Fund the block explorer".to_string(),
+            beneficiary: "grantee".to_string(),
+            requested: Uint128::new(requested),
         };
-        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for (proposal_id, requested) in [("grant", 1000), ("big_grant", 2500)] {
+            let msg = create(proposal_id, requested);
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("grant", 1000),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalAlreadyExists {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("no_grant", 0),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyRequest {});
+        let msg = ExecuteMsg::StakeConviction {
+            proposal_id: "no_grant".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(600, "ujuno")),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotFound {});
 
+        let stake = ExecuteMsg::StakeConviction {
+            proposal_id: "grant".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(600, "uosmo")),
+            stake.clone(),
+        )
+        .unwrap_err();
         assert_eq!(
-            res.attributes,
-            vec![
-                attr("action", "execute_vote"),
-                attr("poll_id", "some_id"),
-                attr("vote", "Juno")
-            ]
+            err,
+            ContractError::Payment(PaymentError::MissingDenom("ujuno".to_string()))
         );
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(600, "ujuno")),
+            stake.clone(),
+        )
+        .unwrap();
+        // Conviction grows with the first stake alone for one block
+        let start = env.block.height;
+        env.block.height = start + 1;
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(400, "ujuno")),
+            stake.clone(),
+        )
+        .unwrap();
 
-        // Change the vote
-        let msg = ExecuteMsg::Vote {
-            poll_id: "some_id".to_string(),
-            vote: "Osmosis".to_string(),
+        // Requesting 10% of the funds with 1000 staked needs a conviction of
+        // 0.0025 * 1000 / ((0.2 - 0.1)^2 * (1 - 0.9)) = 2500
+        let proposal = |deps: Deps, env: Env| -> ConvictionProposalResponse {
+            let msg = QueryMsg::ConvictionProposal {
+                proposal_id: "grant".to_string(),
+            };
+            from_binary(&query(deps, env, msg).unwrap()).unwrap()
         };
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        let res = proposal(deps.as_ref(), env.clone());
+        assert_eq!(res.conviction, Uint128::new(600));
+        assert_eq!(res.threshold, Some(Uint128::new(2500)));
 
+        let execute_grant = ExecuteMsg::ExecuteConviction {
+            proposal_id: "grant".to_string(),
+        };
+        // 600 * 0.9^2 + 1000 * (1 - 0.9^2) / (1 - 0.9) = 2386
+        env.block.height = start + 3;
         assert_eq!(
-            res.attributes,
-            vec![
-                attr("action", "execute_vote"),
-                attr("poll_id", "some_id"),
-                attr("vote", "Osmosis")
-            ]
+            proposal(deps.as_ref(), env.clone()).conviction,
+            Uint128::new(2386)
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            execute_grant.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ConvictionBelowThreshold {});
+        // A request of 20% of the funds or more never passes
+        let msg = ExecuteMsg::ExecuteConviction {
+            proposal_id: "big_grant".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::RequestTooLarge {});
+
+        // 600 * 0.9^3 + 1000 * (1 - 0.9^3) / (1 - 0.9) = 3147
+        env.block.height = start + 4;
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            execute_grant.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "grantee".to_string(),
+                amount: coins(1000, "ujuno"),
+            })]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            execute_grant,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalExecuted {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(1, "ujuno")),
+            stake,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProposalExecuted {});
+
+        // Stakes are returned once they are no longer needed
+        let unstake = |amount| ExecuteMsg::UnstakeConviction {
+            proposal_id: "grant".to_string(),
+            amount: Uint128::new(amount),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unstake(500),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientStake {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            unstake(400),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(400, "ujuno"),
+            })]
         );
+
+        let msg = QueryMsg::ConvictionPool {};
+        let res: ConvictionPoolResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let pool = res.pool.unwrap();
+        assert_eq!(pool.funds, Uint128::new(9000));
+        assert_eq!(pool.total_staked, Uint128::zero());
     }
 
     #[test]
-    fn test_execute_vote_invalid() {
+    fn test_execute_conviction_without_stake() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
+        let mut env = mock_env();
         let info = mock_info(ADDR1, &[]);
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
         let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        // Create the vote, some_id poll is not created yet.
-        let msg = ExecuteMsg::Vote {
-            poll_id: "some_id".to_string(),
-            vote: "Juno".to_string(),
+        let set_pool = |min_threshold_stake| ExecuteMsg::SetConvictionPool {
+            denom: "ujuno".to_string(),
+            decay: Decimal::percent(90),
+            max_ratio: Decimal::percent(20),
+            weight: Decimal::from_ratio(25u64, 10000u64),
+            min_threshold_stake,
         };
-        // Unwrap to assert error
-        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
-        assert_eq!(err, ContractError::PollNotFound {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::zero()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidConvictionPool {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::percent(5)),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::FundConvictionPool {};
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(1000, "ujuno")),
+            msg,
+        )
+        .unwrap();
 
-        // Create the poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id".to_string(),
-            question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
+        // Nothing is staked on the pool, a proposal nobody backs cannot be paid out
+        let msg = ExecuteMsg::CreateConvictionProposal {
+            proposal_id: "grant".to_string(),
+            title: "Pay me".to_string(),
+            beneficiary: "thief".to_string(),
+            requested: Uint128::new(199),
         };
-        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("thief", &[]), msg).unwrap();
+        let execute_grant = ExecuteMsg::ExecuteConviction {
+            proposal_id: "grant".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("thief", &[]),
+            execute_grant.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ConvictionBelowThreshold {});
 
-        // Vote on a now existing poll but the option "DVPN" does not exist
-        let msg = ExecuteMsg::Vote {
-            poll_id: "some_id".to_string(),
-            vote: "DVPN".to_string(),
+        // A dust stake is measured against 5% of the funds:
+        // 0.0025 * 50 / ((0.2 - 0.199)^2 * (1 - 0.9)) = 1250000
+        let msg = ExecuteMsg::StakeConviction {
+            proposal_id: "grant".to_string(),
         };
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-        assert_eq!(err, ContractError::OptionNonExistent {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("thief", &coins(1, "ujuno")),
+            msg,
+        )
+        .unwrap();
+        env.block.height += 1000;
+        let msg = QueryMsg::ConvictionProposal {
+            proposal_id: "grant".to_string(),
+        };
+        let res: ConvictionProposalResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.conviction, Uint128::new(10));
+        assert_eq!(res.threshold, Some(Uint128::new(1250000)));
+        let err = execute(deps.as_mut(), env, mock_info("thief", &[]), execute_grant).unwrap_err();
+        assert_eq!(err, ContractError::ConvictionBelowThreshold {});
     }
 
     #[test]
-    fn test_query_all_polls() {
+    fn test_execute_conviction_overflow() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
+        let mut env = mock_env();
         let info = mock_info(ADDR1, &[]);
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
         let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        // No polls created, query and expect not response from AllPollsResponse
-        let msg = QueryMsg::AllPolls {};
-        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
-        let res: AllPollsResponse = from_binary(&bin).unwrap();
-        assert_eq!(res.polls.len(), 0);
+        let set_pool = |decay, weight| ExecuteMsg::SetConvictionPool {
+            denom: "ujuno".to_string(),
+            decay,
+            max_ratio: Decimal::one(),
+            weight,
+            min_threshold_stake: Decimal::one(),
+        };
+        // A weightless pool would pass every proposal with any conviction
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::percent(90), Decimal::zero()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidConvictionPool {});
 
-        // Create a poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id_1".to_string(),
-            question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
+        // Conviction approaching 10^18 times the stake saturates instead of overflowing
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            set_pool(Decimal::raw(999_999_999_999_999_999), Decimal::one()),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::FundConvictionPool {};
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(10u128.pow(30), "ujuno")),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::CreateConvictionProposal {
+            proposal_id: "grant".to_string(),
+            title: "Fund the block explorer".to_string(),
+            beneficiary: "grantee".to_string(),
+            requested: Uint128::new(10u128.pow(30) - 10u128.pow(21)),
         };
         let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-        // Create a second poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id_2".to_string(),
-            question: "What's your colour?".to_string(),
-            options: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+        let msg = ExecuteMsg::StakeConviction {
+            proposal_id: "grant".to_string(),
         };
-        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(10u128.pow(30), "ujuno")),
+            msg,
+        )
+        .unwrap();
+        env.block.height += u32::MAX as u64;
+        let msg = QueryMsg::ConvictionProposal {
+            proposal_id: "grant".to_string(),
+        };
+        let res: ConvictionProposalResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.conviction, Uint128::MAX);
 
-        // Query
-        let msg = QueryMsg::AllPolls {};
-        let bin = query(deps.as_ref(), env, msg).unwrap();
-        let res: AllPollsResponse = from_binary(&bin).unwrap();
-        assert_eq!(res.polls.len(), 2);
-        assert_eq!(
-            res.polls[0],
-            Poll {
-                creator: Addr::unchecked(ADDR1),
-                question: "What's your favourite Cosmos coin?".to_string(),
-                options: vec![
-                    ("Cosmos Hub".to_string(), 0),
-                    ("Juno".to_string(), 0),
-                    ("Osmosis".to_string(), 0)
-                ],
-            }
-        );
+        // 10^30 staked / ((1 - 0.999999999)^2 * (1 - 0)) does not fit in a threshold
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            set_pool(Decimal::zero(), Decimal::one()),
+        )
+        .unwrap();
+        let res: ConvictionProposalResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.threshold, None);
+        let msg = ExecuteMsg::ExecuteConviction {
+            proposal_id: "grant".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::RequestTooLarge {});
     }
 
     #[test]
-    fn test_query_poll() {
+    fn test_execute_funding_round() {
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &[]);
+        let info = mock_info(ADDR1, &coins(1000, "ujuno"));
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
         let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        // Create a poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id_1".to_string(),
-            question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
+        let round = |mode| {
+            ExecuteMsg::CreateFundingRound(CreatePollMsg {
+                poll_id: "round_1".to_string(),
+                question: "Which public goods should we fund?".to_string(),
+                options: vec!["project_a".to_string()],
+                mode,
+                ..Default::default()
+            })
         };
-        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &coins(1000, "ujuno")),
+            round(VotingMode::SingleChoice),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            round(VotingMode::Borda),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingMode {});
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            round(VotingMode::SingleChoice),
+        )
+        .unwrap();
 
-        // Query for the poll that exists
-        let msg = QueryMsg::Poll {
-            poll_id: "some_id_1".to_string(),
+        // Projects register themselves as options
+        let register = ExecuteMsg::RegisterProject {
+            poll_id: "round_1".to_string(),
         };
-        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
-        let res: PollResponse = from_binary(&bin).unwrap();
-        // Expect a poll
-        assert!(res.poll.is_some());
+        for project in ["project_b", "project_c"] {
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(project, &[]),
+                register.clone(),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("project_b", &[]),
+            register,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ProjectAlreadyRegistered {});
+
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "round_1".to_string(),
+            vote: "project_a".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundingRoundVote {});
+
+        let contribute = |project: &str| ExecuteMsg::Contribute {
+            poll_id: "round_1".to_string(),
+            project: project.to_string(),
+        };
+        let contributions = [
+            ("alice", "project_a", 10),
+            ("alice", "project_a", 15),
+            ("bob", "project_a", 25),
+            ("carol", "project_a", 25),
+            ("dave", "project_a", 25),
+            ("alice", "project_b", 100),
+            ("bob", "project_c", 100),
+            ("carol", "project_c", 25),
+        ];
+        for (contributor, project, amount) in contributions {
+            let _res = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(contributor, &coins(amount, "ujuno")),
+                contribute(project),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &coins(10, "uosmo")),
+            contribute("project_a"),
+        )
+        .unwrap_err();
         assert_eq!(
-            res,
-            PollResponse {
-                poll: Some(Poll {
-                    creator: Addr::unchecked(ADDR1),
-                    question: "What's your favourite Cosmos coin?".to_string(),
-                    options: vec![
-                        ("Cosmos Hub".to_string(), 0),
-                        ("Juno".to_string(), 0),
-                        ("Osmosis".to_string(), 0)
-                    ]
-                })
-            }
+            err,
+            ContractError::Payment(PaymentError::MissingDenom("ujuno".to_string()))
         );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &coins(10, "ujuno")),
+            contribute("project_d"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OptionNonExistent {});
 
-        // Query for the poll that does not exists
+        // Options count the contributors of each project
         let msg = QueryMsg::Poll {
-            poll_id: "some_id_not_exist".to_string(),
+            poll_id: "round_1".to_string(),
         };
-        let bin = query(deps.as_ref(), env, msg).unwrap();
-        let res: PollResponse = from_binary(&bin).unwrap();
-        // Expect none
-        assert!(res.poll.is_none());
+        let res: PollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                ("project_a".to_string(), Uint128::new(4)),
+                ("project_b".to_string(), Uint128::new(1)),
+                ("project_c".to_string(), Uint128::new(2))
+            ]
+        );
+        assert_eq!(poll.vote_count, 7);
+
+        // (5 + 5 + 5 + 5)^2 - 100 = 300, (10)^2 - 100 = 0 and (10 + 5)^2 - 125 = 100,
+        // the pool covers all of it
+        let msg = QueryMsg::FundingRound {
+            poll_id: "round_1".to_string(),
+        };
+        let res: FundingRoundResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.round.unwrap().matching_pool, Uint128::new(1000));
+        let funding = |project: &str, contributed, matched| ProjectFunding {
+            project: project.to_string(),
+            contributed: Uint128::new(contributed),
+            matched: Uint128::new(matched),
+        };
+        assert_eq!(
+            res.projects,
+            vec![
+                funding("project_a", 100, 300),
+                funding("project_b", 100, 0),
+                funding("project_c", 125, 100)
+            ]
+        );
+
+        let distribute = ExecuteMsg::Distribute {
+            poll_id: "round_1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            distribute.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PollNotClosed {});
+        let delete = ExecuteMsg::DeletePoll {
+            poll_id: "round_1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[]),
+            delete.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDistributed {});
+
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "round_1".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &coins(10, "ujuno")),
+            contribute("project_a"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PollClosed {});
+
+        // The unused part of the matching pool goes back to the creator
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            distribute.clone(),
+        )
+        .unwrap();
+        let send = |to: &str, amount| {
+            SubMsg::new(BankMsg::Send {
+                to_address: to.to_string(),
+                amount: coins(amount, "ujuno"),
+            })
+        };
+        assert_eq!(
+            res.messages,
+            vec![
+                send("project_a", 400),
+                send("project_b", 100),
+                send("project_c", 225),
+                send(ADDR1, 600)
+            ]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            distribute,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyDistributed {});
+
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR1, &[]), delete).unwrap();
+        let msg = QueryMsg::FundingRound {
+            poll_id: "round_1".to_string(),
+        };
+        let res: FundingRoundResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.round, None);
+        assert!(res.projects.is_empty());
     }
 
     #[test]
-    fn test_query_vote() {
+    fn test_execute_claim_reward() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
+        let mut env = mock_env();
         let info = mock_info(ADDR1, &[]);
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
         let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        // Create a poll
-        let msg = ExecuteMsg::CreatePoll {
-            poll_id: "some_id_1".to_string(),
+        let create = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
             question: "What's your favourite Cosmos coin?".to_string(),
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string(),
-            ],
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            reward_by_weight: true,
+            reward_claim_period: Some(100),
+            ..Default::default()
+        });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &[coin(900, "ujuno"), coin(900, "uosmo")]),
+            create.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Payment(PaymentError::MultipleDenoms {}));
+        for reward_claim_period in [0, u64::MAX] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                poll_id: "some_id".to_string(),
+                question: "What's your favourite Cosmos coin?".to_string(),
+                options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+                reward_claim_period: Some(reward_claim_period),
+                ..Default::default()
+            });
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(ADDR1, &coins(900, "ujuno")),
+                msg,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidClaimPeriod {});
+        }
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(900, "ujuno")),
+            create,
+        )
+        .unwrap();
+
+        // ADDR2 votes for bob as well, so their ballot weighs 2
+        let msg = ExecuteMsg::Delegate {
+            to: ADDR2.to_string(),
         };
-        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), msg).unwrap();
+        for voter in [ADDR2, "carol"] {
+            let msg = ExecuteMsg::Vote(VoteMsg {
+                poll_id: "some_id".to_string(),
+                vote: "Juno".to_string(),
+                ..Default::default()
+            });
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
 
-        // Create a vote
-        let msg = ExecuteMsg::Vote {
-            poll_id: "some_id_1".to_string(),
+        // The shares are counted as ballots are cast and revoked
+        let reward_shares = |deps: Deps| {
+            let msg = QueryMsg::Reward {
+                poll_id: "some_id".to_string(),
+            };
+            let res: RewardResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.reward.unwrap().total_shares
+        };
+        assert_eq!(reward_shares(deps.as_ref()), Uint128::new(3));
+        let msg = ExecuteMsg::Revoke {
+            sender: Addr::unchecked("carol"),
+            poll_id: "some_id".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(reward_shares(deps.as_ref()), Uint128::new(2));
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
             vote: "Juno".to_string(),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), msg).unwrap();
+        assert_eq!(reward_shares(deps.as_ref()), Uint128::new(3));
+
+        let claim = ExecuteMsg::ClaimReward {
+            poll_id: "some_id".to_string(),
         };
-        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PollNotClosed {});
+        let delete = ExecuteMsg::DeletePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), delete.clone()).unwrap_err();
+        assert_eq!(err, ContractError::RewardOutstanding {});
 
-        // Query for a vote that exists
-        // In this case we check if the ballot has been registered (voter_address, poll_id)
-        let msg = QueryMsg::Vote {
-            poll_id: "some_id_1".to_string(),
-            address: ADDR1.to_string(),
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "some_id".to_string(),
         };
-        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
-        let res: VoteResponse = from_binary(&bin).unwrap();
-        // Expect the vote to exist
-        assert!(res.vote.is_some());
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::Reward {
+            poll_id: "some_id".to_string(),
+        };
+        let res: RewardResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        let reward = res.reward.unwrap();
+        assert_eq!(reward.amount, coin(900, "ujuno"));
+        assert_eq!(reward.total_shares, Uint128::new(3));
         assert_eq!(
-            res,
-            VoteResponse {
-                vote: Some(Ballot {
-                    option: "Juno".to_string()
-                })
-            }
+            reward.claim_deadline,
+            Some(env.block.time.plus_seconds(100))
         );
 
-        // Query for a vote that does not exists
-        let msg = QueryMsg::Vote {
-            poll_id: "some_id_2".to_string(),
-            address: ADDR2.to_string(),
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            claim.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(600, "ujuno"),
+            })]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR2, &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::RewardClaimed {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BallotNotFound {});
+
+        let reclaim = ExecuteMsg::ReclaimReward {
+            poll_id: "some_id".to_string(),
         };
-        let bin = query(deps.as_ref(), env, msg).unwrap();
-        let res: VoteResponse = from_binary(&bin).unwrap();
-        // Expect the vote to not exist
-        assert!(res.vote.is_none());
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), reclaim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::ClaimPeriodNotOver {});
+
+        // carol's share goes back to the creator once the claim period is over
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("carol", &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ClaimPeriodOver {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("carol", &[]),
+            reclaim.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), reclaim).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADDR1.to_string(),
+                amount: coins(300, "ujuno"),
+            })]
+        );
+        let res: RewardResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.reward, None);
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), claim).unwrap_err();
+        assert_eq!(err, ContractError::NoReward {});
+        let _res = execute(deps.as_mut(), env, info, delete).unwrap();
     }
 
     #[test]
-    fn test_query_config() {
+    fn test_execute_claim_reward_without_shares() {
         let mut deps = mock_dependencies();
-        let env = mock_env();
+        let mut env = mock_env();
         let info = mock_info(ADDR1, &[]);
         // Instantiate the contract
         let msg = InstantiateMsg { admin: None };
-        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let msg = QueryMsg::Config {};
-        let bin = query(deps.as_ref(), env, msg).unwrap();
-        let res: ConfigResponse = from_binary(&bin).unwrap();
-        assert_eq!(res.admin, Addr::unchecked(ADDR1));
+        // ADDR2's leaf commits to a weight of 0
+        let (root, proofs) = merkle_tree(&[ADDR1, "addr20"]);
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "some_id".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            weighting: Weighting::Merkle { merkle_root: root },
+            reward_by_weight: true,
+            reward_claim_period: Some(100),
+            ..Default::default()
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(ADDR1, &coins(900, "ujuno")),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Vote(VoteMsg {
+            poll_id: "some_id".to_string(),
+            vote: "Juno".to_string(),
+            proof: proofs[1].clone(),
+            weight: Some(0),
+            ..Default::default()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ClosePoll {
+            poll_id: "some_id".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ClaimReward {
+            poll_id: "some_id".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoRewardShares {});
+
+        // The whole reward goes back to the creator
+        env.block.time = env.block.time.plus_seconds(100);
+        let msg = ExecuteMsg::ReclaimReward {
+            poll_id: "some_id".to_string(),
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: ADDR1.to_string(),
+                amount: coins(900, "ujuno"),
+            })]
+        );
     }
 }
@@ -0,0 +1,44 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::state::ConvictionPool;
+
+// Conviction `blocks` blocks after it was `conviction` with `staked` tokens behind it.
+// Every block keeps `decay` of the conviction and adds the stake, so conviction approaches
+// staked / (1 - decay):
+// y(n) = decay^n * y(0) + staked * (1 - decay^n) / (1 - decay)
+// Saturates at Uint128::MAX, which no threshold exceeds.
+pub fn grow(conviction: Uint128, staked: Uint128, decay: Decimal, blocks: u64) -> Uint128 {
+    let kept = decay.pow(blocks.min(u32::MAX as u64) as u32);
+    let added = staked
+        .checked_multiply_ratio(
+            (Decimal::one() - kept).atomics(),
+            (Decimal::one() - decay).atomics(),
+        )
+        .unwrap_or(Uint128::MAX);
+    (conviction * kept).saturating_add(added)
+}
+
+// Conviction a proposal requesting `requested` of the pool funds needs to pass:
+// weight * staked / ((max_ratio - requested / funds)^2 * (1 - decay))
+// where staked is total_staked but at least min_threshold_stake of the funds, so a proposal
+// cannot pass while little to nothing is staked.
+// None when the request is max_ratio of the funds or more, or the threshold does not fit in a
+// Uint128, it can never pass.
+pub fn threshold(pool: &ConvictionPool, requested: Uint128) -> Option<Uint128> {
+    if pool.funds.is_zero() {
+        return None;
+    }
+    let ratio = Decimal::from_ratio(requested, pool.funds);
+    if ratio >= pool.max_ratio {
+        return None;
+    }
+    let margin = pool.max_ratio - ratio;
+    let denominator = margin * margin * (Decimal::one() - pool.decay);
+    if denominator.is_zero() {
+        return None;
+    }
+    let staked = pool.total_staked.max(pool.funds * pool.min_threshold_stake);
+    staked
+        .checked_multiply_ratio(pool.weight.atomics(), denominator.atomics())
+        .ok()
+}
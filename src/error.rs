@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,15 +7,189 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Too many poll options")]
     TooManyOptions {},
 
+    #[error("Option name is reserved for a built-in choice")]
+    ReservedOption {},
+
+    #[error("Too many poll tags")]
+    TooManyTags {},
+
+    #[error("Invalid poll tag")]
+    InvalidTag {},
+
+    #[error("A poll with this id already exists")]
+    PollAlreadyExists {},
+
+    #[error("Invalid merkle root")]
+    InvalidMerkleRoot {},
+
+    #[error("Invalid merkle proof")]
+    InvalidProof {},
+
     #[error("The Poll does not exist")]
     PollNotFound {},
 
+    #[error("The poll already has ballots")]
+    PollHasBallots {},
+
+    #[error("The sender is not eligible to vote on this poll")]
+    NotEligible {},
+
+    #[error("The sender already voted and the poll does not allow vote changes")]
+    AlreadyVoted {},
+
+    #[error("Cannot delegate to yourself")]
+    InvalidDelegate {},
+
+    #[error("The sender has no delegation")]
+    DelegationNotFound {},
+
+    #[error("Invalid voting mode parameters")]
+    InvalidVotingMode {},
+
+    #[error("Invalid vote allocation for this poll")]
+    InvalidAllocation {},
+
+    #[error("Invalid ranking for this poll")]
+    InvalidRanking {},
+
+    #[error("The allocation must use exactly the poll's point budget")]
+    AllocationBudgetMismatch {},
+
+    #[error("The allocation costs more credits than available")]
+    InsufficientCredits {},
+
     #[error("The option does not exist")]
     OptionNonExistent {},
+
+    #[error("The ballot does not exist")]
+    BallotNotFound {},
+
+    #[error("The poll is closed")]
+    PollClosed {},
+
+    #[error("Only a passed proposal can be executed")]
+    ProposalNotPassed {},
+
+    #[error("The poll is still open")]
+    PollNotClosed {},
+
+    #[error("The poll is not linked to a governance proposal")]
+    NoGovProposal {},
+
+    #[error("The sender has no bonded stake")]
+    NoStake {},
+
+    #[error("The poll is not stake weighted")]
+    NotStakeWeighted {},
+
+    #[error("The sender does not own the token")]
+    NotTokenOwner {},
+
+    #[error("A token can only be listed once")]
+    DuplicateToken {},
+
+    #[error("The sender holds less than the poll's minimum balance")]
+    InsufficientBalance {},
+
+    #[error("Lock duration must be between 1 second and the maximum lock duration")]
+    InvalidLockDuration {},
+
+    #[error("The lock holds a different denom")]
+    LockDenomMismatch {},
+
+    #[error("The sender has no lock for this poll")]
+    NoLock {},
+
+    #[error("The lock has not expired yet")]
+    LockNotExpired {},
+
+    #[error("The lock weighs ballots on open polls")]
+    LockInUse {},
+
+    #[error("The conviction pool is not set up")]
+    ConvictionPoolNotFound {},
+
+    #[error("Invalid conviction pool parameters")]
+    InvalidConvictionPool {},
+
+    #[error("The proposal requests too much of the pool")]
+    RequestTooLarge {},
+
+    #[error("The proposal's conviction is below its threshold")]
+    ConvictionBelowThreshold {},
+
+    #[error("The sender has not staked that much")]
+    InsufficientStake {},
+
+    #[error("The poll is not a funding round")]
+    NotFundingRound {},
+
+    #[error("Funding rounds take contributions instead of votes")]
+    FundingRoundVote {},
+
+    #[error("The project is already registered")]
+    ProjectAlreadyRegistered {},
+
+    #[error("The funding round has already been distributed")]
+    AlreadyDistributed {},
+
+    #[error("The funding round has not been distributed yet")]
+    NotDistributed {},
+
+    #[error("The poll has no reward")]
+    NoReward {},
+
+    #[error("The sender already claimed their reward")]
+    RewardClaimed {},
+
+    #[error("The reward claim period is over")]
+    ClaimPeriodOver {},
+
+    #[error("The reward claim period is not over yet")]
+    ClaimPeriodNotOver {},
+
+    #[error("The poll's reward has not been reclaimed yet")]
+    RewardOutstanding {},
+
+    #[error("Cannot migrate from {contract} version {version}")]
+    InvalidMigration { contract: String, version: String },
+
+    #[error("The ballot would overflow the poll's tally")]
+    TallyOverflow {},
+
+    #[error("The contract already voted on the governance proposal")]
+    GovVoteCast {},
+
+    #[error("The conviction proposal already exists")]
+    ProposalAlreadyExists {},
+
+    #[error("The conviction proposal does not exist")]
+    ProposalNotFound {},
+
+    #[error("The conviction proposal was already paid out")]
+    ProposalExecuted {},
+
+    #[error("The proposal must request funds")]
+    EmptyRequest {},
+
+    #[error("No ballot earned a share of the reward")]
+    NoRewardShares {},
+
+    #[error("The reward claim period must be between 1 second and the maximum claim period")]
+    InvalidClaimPeriod {},
+
+    #[error("Polls voted with cw721 tokens cannot carry a reward")]
+    TokenPollReward {},
+
+    #[error("The delegate already votes for the maximum number of delegators")]
+    TooManyDelegators {},
 }
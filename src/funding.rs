@@ -0,0 +1,37 @@
+use cosmwasm_std::{Decimal256, StdResult, Uint128, Uint256};
+
+// Capital constrained quadratic funding (CLR) matching.
+// A project funded with contributions c_i deserves (sum sqrt(c_i))^2 - sum c_i from the pool,
+// when the pool cannot cover every project they are all scaled down by the same ratio.
+pub fn matches(projects: &[Vec<Uint128>], pool: Uint128) -> StdResult<Vec<Uint128>> {
+    let ideal: Vec<Uint256> = projects
+        .iter()
+        .map(|contributions| ideal_match(contributions))
+        .collect();
+    let total = ideal.iter().fold(Uint256::zero(), |total, m| total + *m);
+
+    ideal
+        .into_iter()
+        .map(|m| {
+            let m = if total <= pool.into() {
+                m
+            } else {
+                m.multiply_ratio(pool, total)
+            };
+            Ok(Uint128::try_from(m)?)
+        })
+        .collect()
+}
+
+fn ideal_match(contributions: &[Uint128]) -> Uint256 {
+    let (roots, sum) =
+        contributions
+            .iter()
+            .fold((Decimal256::zero(), Uint256::zero()), |(roots, sum), c| {
+                (
+                    roots + Decimal256::from_ratio(*c, 1u8).sqrt(),
+                    sum + Uint256::from(*c),
+                )
+            });
+    (roots * roots).to_uint_floor().saturating_sub(sum)
+}
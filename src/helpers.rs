@@ -1,46 +1,105 @@
-// use schemars::JsonSchema;
-// use serde::{Deserialize, Serialize};
-//
-// use cosmwasm_std::{
-//     to_binary, Addr, CosmosMsg, CustomQuery, Querier, QuerierWrapper, StdResult, WasmMsg, WasmQuery,
-// };
-//
-// use crate::msg::{CustomResponse, ExecuteMsg, QueryMsg};
-//
-// /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
-// /// for working with this. Rename it to your contract name.
-// #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-// pub struct CwTemplateContract(pub Addr);
-//
-// impl CwTemplateContract {
-//     pub fn addr(&self) -> Addr {
-//         self.0.clone()
-//     }
-//
-//     pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
-//         let msg = to_binary(&msg.into())?;
-//         Ok(WasmMsg::Execute {
-//             contract_addr: self.addr().into(),
-//             msg,
-//             funds: vec![],
-//         }
-//         .into())
-//     }
-//
-//     /// Get Custom
-//     pub fn custom_query<Q, T, CQ>(&self, querier: &Q, val: String) -> StdResult<CustomResponse>
-//     where
-//         Q: Querier,
-//         T: Into<String>,
-//         CQ: CustomQuery,
-//     {
-//         let msg = QueryMsg::CustomMsg { val };
-//         let query = WasmQuery::Smart {
-//             contract_addr: self.addr().into(),
-//             msg: to_binary(&msg)?,
-//         }
-//         .into();
-//         let res: CustomResponse = QuerierWrapper::<CQ>::new(querier).query(&query)?;
-//         Ok(res)
-//     }
-// }
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_binary, Addr, CosmosMsg, CustomQuery, QuerierWrapper, StdResult, WasmMsg};
+use serde::de::DeserializeOwned;
+
+use crate::msg::{
+    AllPollsResponse, ConfigResponse, CreatePollMsg, ExecuteMsg, PollResponse, QueryMsg, VoteMsg,
+    VoteResponse,
+};
+
+/// PollContract is a wrapper around Addr that provides a lot of helpers
+/// for working with this contract from other contracts and tests.
+#[cw_serde]
+pub struct PollContract(pub Addr);
+
+impl PollContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        let msg = to_binary(&msg.into())?;
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    pub fn create_poll(&self, msg: CreatePollMsg) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CreatePoll(msg))
+    }
+
+    pub fn vote(&self, msg: VoteMsg) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Vote(msg))
+    }
+
+    pub fn delete_poll(&self, poll_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::DeletePoll {
+            poll_id: poll_id.into(),
+        })
+    }
+
+    pub fn close_poll(&self, poll_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ClosePoll {
+            poll_id: poll_id.into(),
+        })
+    }
+
+    pub fn cast_gov_vote(&self, poll_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CastGovVote {
+            poll_id: poll_id.into(),
+        })
+    }
+
+    fn query<CQ, T>(&self, querier: &QuerierWrapper<CQ>, msg: &QueryMsg) -> StdResult<T>
+    where
+        CQ: CustomQuery,
+        T: DeserializeOwned,
+    {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    /// Get a single poll, `poll` is None if it does not exist
+    pub fn poll<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        poll_id: impl Into<String>,
+    ) -> StdResult<PollResponse> {
+        let msg = QueryMsg::Poll {
+            poll_id: poll_id.into(),
+        };
+        self.query(querier, &msg)
+    }
+
+    /// Get every poll stored in the contract
+    pub fn all_polls<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<AllPollsResponse> {
+        self.query(querier, &QueryMsg::AllPolls {})
+    }
+
+    /// Get the ballot `address` cast in a poll, `vote` is None if they did not vote
+    pub fn ballot<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        poll_id: impl Into<String>,
+        address: impl Into<String>,
+    ) -> StdResult<VoteResponse> {
+        let msg = QueryMsg::Vote {
+            poll_id: poll_id.into(),
+            address: address.into(),
+        };
+        self.query(querier, &msg)
+    }
+
+    /// Get the contract config
+    pub fn config<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<ConfigResponse> {
+        self.query(querier, &QueryMsg::Config {})
+    }
+}
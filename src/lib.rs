@@ -1,7 +1,11 @@
 pub mod contract;
+mod conviction;
 mod error;
+mod funding;
 pub mod helpers;
+mod merkle;
 pub mod msg;
 pub mod state;
+mod tally;
 
 pub use crate::error::ContractError;
@@ -0,0 +1,40 @@
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+
+// A merkle root is a hex encoded sha256 hash
+pub fn validate_root(root: &str) -> Result<(), ContractError> {
+    let mut buf = [0; 32];
+    hex::decode_to_slice(root, &mut buf).map_err(|_| ContractError::InvalidMerkleRoot {})
+}
+
+// Check that `address` (and `weight` if any) is a leaf of the tree with the given root.
+// Pairs are sorted before hashing so the proof does not need to carry left/right positions.
+pub fn verify(
+    root: &str,
+    address: &str,
+    weight: Option<u64>,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let leaf = match weight {
+        Some(weight) => format!("{}{}", address, weight),
+        None => address.to_string(),
+    };
+    let hash: [u8; 32] = Sha256::digest(leaf.as_bytes()).into();
+
+    let hash = proof.iter().try_fold(hash, |hash, sibling| {
+        let mut sibling_buf = [0; 32];
+        hex::decode_to_slice(sibling, &mut sibling_buf)
+            .map_err(|_| ContractError::InvalidProof {})?;
+        let mut hashes = [hash, sibling_buf];
+        hashes.sort_unstable();
+        Ok::<_, ContractError>(Sha256::digest(hashes.concat()).into())
+    })?;
+
+    let mut root_buf = [0; 32];
+    hex::decode_to_slice(root, &mut root_buf).map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    if root_buf != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+    Ok(())
+}
@@ -1,73 +1,372 @@
-use cosmwasm_std::Addr;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Coin, Decimal, Decimal256, Uint128};
 
-use crate::state::{Ballot, Poll};
+use crate::state::{
+    Ballot, ConvictionPool, ConvictionProposal, FundingRound, Lock, Poll, Reward, VotingMode,
+    Weighting,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
+#[derive(Default)]
+pub struct CreatePollMsg {
+    pub poll_id: String,
+    pub question: String,
+    // Must be empty for Proposal polls which have fixed options
+    pub options: Vec<String>,
+    // Up to MAX_TAGS labels used to group polls, see QueryMsg::PollsByTag
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // When set only these addresses can vote, the list is managed with AddVoters/RemoveVoters
+    pub eligible_voters: Option<Vec<String>>,
+    // Whether voters can change their ballot, defaults to true
+    pub allow_vote_change: Option<bool>,
+    // Enable the built-in "abstain" and "none_of_the_above" votes.
+    // Proposal polls always allow abstain and never none_of_the_above.
+    #[serde(default)]
+    pub allow_abstain: bool,
+    #[serde(default)]
+    pub allow_none_of_the_above: bool,
+    #[serde(default)]
+    pub mode: VotingMode,
+    // Mirror a chain governance proposal, see ExecuteMsg::CastGovVote. Proposal polls only.
+    pub gov_proposal_id: Option<u64>,
+    // Defaults to one address one vote
+    #[serde(default)]
+    pub weighting: Weighting,
+    // Only addresses holding at least this balance can vote
+    pub min_balance: Option<Coin>,
+    // (cw20 contract, amount) only addresses holding at least this many tokens can vote
+    pub min_cw20_balance: Option<(String, Uint128)>,
+    // Funds sent with CreatePoll are a reward for voting, see ExecuteMsg::ClaimReward.
+    // Every ballot gets the same share, or a share proportional to its weight when set.
+    // Cw721 polls cannot carry a reward.
+    #[serde(default)]
+    pub reward_by_weight: bool,
+    // Seconds voters have to claim once the poll closes, defaults to DEFAULT_CLAIM_PERIOD
+    // and at most MAX_CLAIM_PERIOD
+    pub reward_claim_period: Option<u64>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct VoteMsg {
+    pub poll_id: String,
+    pub vote: String,
+    // Hex encoded sibling hashes from the sender's leaf up to the poll's merkle root.
+    // The leaf is sha256(address), or sha256(address + weight) when a weight is given.
+    #[serde(default)]
+    pub proof: Vec<String>,
+    // Weight committed to in the sender's leaf, only valid on Merkle weighted polls
+    pub weight: Option<u64>,
+    // (option, votes) on Quadratic polls, (option, score) on Score polls,
+    // (option, points) on Allocation polls.
+    // `vote` is ignored for those, except to cast an enabled built-in choice with no allocation.
+    #[serde(default)]
+    pub allocation: Vec<(String, u64)>,
+    // Options from most to least preferred on ranked polls (Stv, Condorcet, Borda),
+    // `vote` is ignored for those, except to cast an enabled built-in choice with no ranking.
+    // Options left out are not ranked at all.
+    #[serde(default)]
+    pub ranking: Vec<String>,
+    // Tokens owned by the sender casting the ballot on NFT gated polls, ignored on other polls
+    #[serde(default)]
+    pub token_ids: Vec<String>,
+}
+
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
-    CreatePoll {
+    CreatePoll(CreatePollMsg),
+    Vote(VoteMsg),
+    // Only the poll creator can edit the question and options, and only before the first vote.
+    UpdatePoll {
         poll_id: String,
         question: String,
         options: Vec<String>,
     },
-    Vote {
+    // Only the poll creator can manage who is eligible to vote.
+    // Adding voters to an unrestricted poll restricts it to the listed addresses.
+    AddVoters {
         poll_id: String,
-        vote: String,
+        voters: Vec<String>,
     },
+    // Ballots already cast by removed voters are kept.
+    RemoveVoters {
+        poll_id: String,
+        voters: Vec<String>,
+    },
+    // Let `to` vote on behalf of the sender on every poll the sender does not vote on.
+    // Replaces any previous delegation. A delegate can vote for at most 50 delegators.
+    Delegate {
+        to: String,
+    },
+    // Ballots already cast by the delegate keep counting the sender.
+    Undelegate {},
     // The key in POLL is poll_id.
-    // Not implemented, created as a follow up exercise from Course - Part 8 - ExecuteMsg
+    // Only the poll creator or the admin can delete a poll, its ballots are removed too.
+    // Each call removes at most 100 ballots and other entries of the poll, the poll stays
+    // Deleting until a call reports complete=true.
     DeletePoll {
         poll_id: String,
     },
-    // The key in BALLOT is [Addr (Sender) , String (Poll_ID)]
-    // Not implemented, created as a follow up exercise from Course - Part 8 - ExecuteMsg
+    // Only the poll creator or the admin can close a poll, ballots can no longer be cast
+    // or revoked. Proposal polls are decided on close.
+    ClosePoll {
+        poll_id: String,
+    },
+    // Mark a passed proposal as executed, only the poll creator or the admin can.
+    ExecuteProposal {
+        poll_id: String,
+    },
+    // Cast the contract's own vote on the linked chain proposal once the poll is decided.
+    // Passed votes yes, Rejected votes no and Vetoed votes no_with_veto.
+    // Anyone can relay the result once, a passed proposal becomes Executed.
+    CastGovVote {
+        poll_id: String,
+    },
+    // Re-weigh the sender's ballot on a stake weighted poll with their current bonded stake
+    RefreshWeight {
+        poll_id: String,
+    },
+    // Lock the sent funds for `duration` seconds, at most MAX_LOCK_DURATION.
+    // Adding to an existing lock keeps the later end time, the denom must match.
+    // A lock created or topped up after a poll started does not weigh on it.
+    Lock {
+        duration: u64,
+    },
+    // Withdraw an expired lock, the sender cannot have ballots on open lock weighted polls
+    Unlock {},
+    // Only the admin can set up the conviction voting pool.
+    // The denom can only change while nothing is funded or staked.
+    SetConvictionPool {
+        denom: String,
+        decay: Decimal,
+        max_ratio: Decimal,
+        weight: Decimal,
+        // Share of the funds thresholds count as staked at least, between 0 (excluded) and 1
+        min_threshold_stake: Decimal,
+    },
+    // Add the sent funds to the conviction voting pool
+    FundConvictionPool {},
+    CreateConvictionProposal {
+        proposal_id: String,
+        title: String,
+        beneficiary: String,
+        requested: Uint128,
+    },
+    // Stake the sent funds on an open proposal, conviction grows with every block
+    StakeConviction {
+        proposal_id: String,
+    },
+    UnstakeConviction {
+        proposal_id: String,
+        amount: Uint128,
+    },
+    // Pay out a proposal whose conviction reached its threshold, anyone can call it
+    ExecuteConviction {
+        proposal_id: String,
+    },
+    // Only the admin can open a quadratic funding round, the sent funds are its matching pool.
+    // The options are the project addresses, only plain SingleChoice polls can be funding rounds.
+    CreateFundingRound(CreatePollMsg),
+    // Add the sender as a project of an open funding round
+    RegisterProject {
+        poll_id: String,
+    },
+    // Give the sent funds to a project of an open funding round
+    Contribute {
+        poll_id: String,
+        project: String,
+    },
+    // Pay every project of a closed funding round its contributions and its match.
+    // What is left of the matching pool goes back to the poll creator. Anyone can call it.
+    Distribute {
+        poll_id: String,
+    },
+    // Claim the sender's share of a closed poll's reward, their ballot must be in BALLOTS
+    ClaimReward {
+        poll_id: String,
+    },
+    // Send what is left of the reward back to the poll creator once the claim period is over.
+    // Only the poll creator or the admin can.
+    ReclaimReward {
+        poll_id: String,
+    },
+    // The key in BALLOT is [String (Poll_ID), Addr (Sender)]
+    // A voter can revoke their own ballot if the poll allows vote changes,
+    // the admin can revoke any ballot.
     Revoke {
         sender: Addr,
         poll_id: String,
     },
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
+#[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(AllPollsResponse)]
     AllPolls {},
+    #[returns(PollResponse)]
     Poll { poll_id: String },
+    #[returns(VoteResponse)]
     Vote { poll_id: String, address: String },
+    // Ballot cast with a token on an NFT gated poll
+    #[returns(VoteResponse)]
+    TokenVote { poll_id: String, token_id: String },
+    // Options ranked by their result, see ResultsResponse
+    #[returns(ResultsResponse)]
+    Results { poll_id: String },
+    // Run the STV count of an Stv poll
+    #[returns(ElectionResultResponse)]
+    ElectionResult { poll_id: String },
+    // Pairwise preferences of a Condorcet poll and its winner
+    #[returns(PairwiseResponse)]
+    Pairwise { poll_id: String },
+    #[returns(LockResponse)]
+    Lock { address: String },
+    #[returns(ConvictionPoolResponse)]
+    ConvictionPool {},
+    // A conviction proposal with its conviction and threshold at the current block
+    #[returns(ConvictionProposalResponse)]
+    ConvictionProposal { proposal_id: String },
+    // Contributions to a funding round and the matches they earn so far
+    #[returns(FundingRoundResponse)]
+    FundingRound { poll_id: String },
+    #[returns(RewardResponse)]
+    Reward { poll_id: String },
+    #[returns(ConfigResponse)]
     Config {},
+    // Polls carrying `tag`, ordered by poll_id
+    #[returns(PollsByTagResponse)]
+    PollsByTag {
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Every tag in use with the number of polls carrying it
+    #[returns(TagsResponse)]
+    Tags {},
+    // Who `address` delegates to and who delegates to `address`
+    #[returns(DelegationsResponse)]
+    Delegations { address: String },
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub struct AllPollsResponse {
     pub polls: Vec<Poll>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub struct PollResponse {
     pub poll: Option<Poll>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub struct VoteResponse {
     pub vote: Option<Ballot>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
+pub struct ResultsResponse {
+    // (option, result) from best to worst, ties keep the order the options were created in.
+    // The result is the vote count, or the average score on Score polls.
+    pub results: Vec<(String, Decimal256)>,
+    // Tallies of the built-in choices, None when the poll does not enable them.
+    // None of the above wins when it outnumbers every option.
+    pub abstain: Option<Uint128>,
+    pub none_of_the_above: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct ElectionResultResponse {
+    // Elected options in the order they were elected
+    pub elected: Vec<String>,
+    pub rounds: Vec<StvRound>,
+}
+
+#[cw_serde]
+pub struct StvRound {
+    // (option, votes) of the options still in the count at the start of the round
+    pub counts: Vec<(String, Decimal256)>,
+    pub elected: Vec<String>,
+    pub eliminated: Option<String>,
+}
+
+#[cw_serde]
+pub struct PairwiseResponse {
+    pub options: Vec<String>,
+    // matrix[i][j] is the weighted number of ballots ranking options[i] above options[j]
+    pub matrix: Vec<Vec<Uint128>>,
+    // The option beating every other option head to head, if there is one
+    pub condorcet_winner: Option<String>,
+    // Schulze winners, the Condorcet winner alone when there is one, several on a tie
+    pub winners: Vec<String>,
+}
+
+#[cw_serde]
+pub struct LockResponse {
+    pub lock: Option<Lock>,
+}
+
+#[cw_serde]
+pub struct ConvictionPoolResponse {
+    pub pool: Option<ConvictionPool>,
+}
+
+#[cw_serde]
+pub struct ConvictionProposalResponse {
+    pub proposal: Option<ConvictionProposal>,
+    pub conviction: Uint128,
+    // None when the request is too large to ever pass
+    pub threshold: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct FundingRoundResponse {
+    pub round: Option<FundingRound>,
+    pub projects: Vec<ProjectFunding>,
+}
+
+#[cw_serde]
+pub struct ProjectFunding {
+    pub project: String,
+    pub contributed: Uint128,
+    // Share of the matching pool
+    pub matched: Uint128,
+}
+
+#[cw_serde]
+pub struct RewardResponse {
+    pub reward: Option<Reward>,
+}
+
+#[cw_serde]
+pub struct PollsByTagResponse {
+    // (poll_id, poll)
+    pub polls: Vec<(String, Poll)>,
+}
+
+#[cw_serde]
+pub struct TagsResponse {
+    // (tag, number of polls)
+    pub tags: Vec<(String, u64)>,
+}
+
+#[cw_serde]
+pub struct DelegationsResponse {
+    pub delegate: Option<Addr>,
+    pub delegators: Vec<Addr>,
+}
+
+#[cw_serde]
 pub struct ConfigResponse {
     pub admin: Addr,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum MigrateMsg {}
+#[cw_serde]
+pub struct MigrateMsg {}
@@ -1,33 +1,288 @@
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-
-use cosmwasm_std::Addr;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cw_serde]
 pub struct Config {
     pub admin: Addr,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cw_serde]
 pub struct Poll {
     pub creator: Addr,
     pub question: String,
-    pub options: Vec<(String, u64)>,
+    pub options: Vec<(String, Uint128)>,
+    pub tags: Vec<String>,
+    // Number of addresses with a ballot in BALLOTS for this poll
+    pub vote_count: u64,
+    // Only addresses in ELIGIBLE_VOTERS can vote
+    pub restricted: bool,
+    // When false a ballot is final and cannot be changed or revoked by the voter
+    pub allow_vote_change: bool,
+    // Built-in choices, None when the creator did not enable them.
+    // Abstentions count towards turnout but not towards any option.
+    pub abstain: Option<Uint128>,
+    pub none_of_the_above: Option<Uint128>,
+    pub mode: VotingMode,
+    // Weighted number of ratings each option received, in `options` order. Score polls only.
+    pub score_counts: Vec<Uint128>,
+    // pairwise[i][j] is the weighted number of ballots ranking option i above option j,
+    // in `options` order. Condorcet polls only.
+    pub pairwise: Vec<Vec<Uint128>>,
+    pub status: PollStatus,
+    // Chain governance proposal the contract votes on with the result, Proposal polls only
+    pub gov_proposal_id: Option<u64>,
+    // Set once the contract voted on the chain proposal, it only votes once
+    pub gov_vote_cast: bool,
+    pub weighting: Weighting,
+    // Voters must hold at least this much of a native denom
+    pub min_balance: Option<Coin>,
+    // (cw20 contract, amount) voters must hold at least this much of a cw20 token
+    pub min_cw20_balance: Option<(Addr, Uint128)>,
+    // Block time the poll was created at
+    pub start_time: Timestamp,
+}
+
+// Polls are open for voting until closed.
+// Proposal polls close into Passed, Rejected or Vetoed, and a Passed proposal can be Executed.
+// A poll is Deleting while DeletePoll removes its ballots over several calls.
+#[cw_serde]
+pub enum PollStatus {
+    Open,
+    Closed,
+    Passed,
+    Rejected,
+    Vetoed,
+    Executed,
+    Deleting,
+}
+
+// How a ballot is cast and counted.
+// Credit budgets, scores and point budgets are at most 1_000_000_000.
+#[cw_serde]
+#[derive(Default)]
+pub enum VotingMode {
+    // One vote for a single option
+    #[default]
+    SingleChoice,
+    // Votes are spread across options, casting n votes on an option costs n^2 credits
+    Quadratic {
+        credits_per_voter: u64,
+    },
+    // Every option can be rated between min and max, `options` holds the sum of scores
+    Score {
+        min: u64,
+        max: u64,
+    },
+    // A ballot splits exactly total_points across options, `options` holds the points received
+    Allocation {
+        total_points: u64,
+    },
+    // Ranked ballots electing `seats` options with Single Transferable Vote,
+    // `options` holds the first preferences, see QueryMsg::ElectionResult
+    Stv {
+        seats: u64,
+    },
+    // Ranked ballots compared head to head, `options` holds the first preferences,
+    // see QueryMsg::Pairwise
+    Condorcet,
+    // Ranked ballots of k options award k-1, k-2, ... 0 points, `options` holds the points
+    Borda,
+    // Chain governance style proposal with the fixed options yes, no, no_with_veto and the
+    // built-in abstain. Closing the poll decides it like the cosmos-sdk gov module:
+    // - turnout / electorate must reach the quorum, otherwise it is rejected
+    // - no_with_veto / turnout above the veto threshold vetoes it
+    // - yes / (turnout - abstain) above the threshold passes it, otherwise it is rejected
+    Proposal {
+        quorum: Decimal,
+        threshold: Decimal,
+        veto_threshold: Decimal,
+        // Total voting power the quorum is measured against
+        electorate: Uint128,
+    },
 }
 
+// Who can vote and how much a ballot weighs.
+// Delegation is only available on Equal polls.
+#[cw_serde]
+#[derive(Default)]
+pub enum Weighting {
+    // One address one vote
+    #[default]
+    Equal,
+    // Hex encoded sha256 merkle root of the allowed voters, see VoteMsg::proof.
+    // A ballot weighs the weight committed to in the voter's leaf, 1 when there is none.
+    Merkle {
+        merkle_root: String,
+    },
+    // A ballot weighs the voter's total bonded stake, queried when the ballot is cast.
+    StakeWeighted,
+    // Only holders of this cw721 collection can vote, one ballot per token in TOKEN_BALLOTS
+    Cw721 {
+        cw721_addr: Addr,
+    },
+    // A ballot weighs the voter's lock of this denom: amount * lock time remaining at the
+    // poll start / MAX_LOCK_DURATION, locks changed after the poll start do not count.
+    // See ExecuteMsg::Lock.
+    Lock {
+        denom: String,
+    },
+}
+
+// Reserved vote values for the built-in choices
+pub const ABSTAIN: &str = "abstain";
+pub const NONE_OF_THE_ABOVE: &str = "none_of_the_above";
+
+// Options of Proposal polls, abstain is the built-in choice
+pub const YES: &str = "yes";
+pub const NO: &str = "no";
+pub const NO_WITH_VETO: &str = "no_with_veto";
+
 // What option the voter chose.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cw_serde]
 pub struct Ballot {
     pub option: String,
+    // How much the ballot counts towards the option, 1 unless proven otherwise or the
+    // voter's bonded stake on stake weighted polls, plus one for each delegator the ballot
+    // was cast on behalf of
+    pub weight: Uint128,
+    // Delegators without a ballot of their own when this ballot was cast
+    pub delegators: Vec<Addr>,
+    // (option, votes or score) for modes spreading a ballot across options, empty otherwise
+    pub allocation: Vec<(String, u64)>,
+    // Options from most to least preferred for ranked modes, empty otherwise
+    pub ranking: Vec<String>,
 }
 
 // A map with a String key and Poll value.
 // The key will be a UUID generated clientside
 pub const POLLS: Map<String, Poll> = Map::new("polls");
 
+// String - Tag
+// String - Poll UUID carrying the tag
+pub const POLL_TAGS: Map<(String, String), Empty> = Map::new("poll_tags");
+
+// Number of polls carrying each tag
+pub const TAG_COUNTS: Map<String, u64> = Map::new("tag_counts");
+
+// String - Poll UUID
+// Addr - Address allowed to vote on a restricted poll
+pub const ELIGIBLE_VOTERS: Map<(String, Addr), Empty> = Map::new("eligible_voters");
+
+// Delegator -> the delegate voting on their behalf
+pub const DELEGATIONS: Map<Addr, Addr> = Map::new("delegations");
+
+// Addr - Delegate
+// Addr - Delegator
+pub const DELEGATORS: Map<(Addr, Addr), Empty> = Map::new("delegators");
+
+// String - Poll UUID
+// Addr - Delegator counted in a delegate's ballot, the value is the delegate
+pub const DELEGATED_VOTES: Map<(String, Addr), Addr> = Map::new("delegated_votes");
+
 pub const CONFIG: Item<Config> = Item::new("config");
 
+// Funds an address locked to weigh in on lock weighted polls
+#[cw_serde]
+pub struct Lock {
+    pub amount: Coin,
+    // Block time the lock was created or last topped up at, it only weighs on polls
+    // started from then on
+    pub start: Timestamp,
+    // The funds can be withdrawn from this time on
+    pub end: Timestamp,
+}
+
+pub const LOCKS: Map<Addr, Lock> = Map::new("locks");
+
+// Addr - Voter
+// String - Poll UUID of a lock weighted poll the voter has a ballot on
+pub const LOCK_VOTES: Map<(Addr, String), Empty> = Map::new("lock_votes");
+
+// String - Poll UUID this vote is for.
 // Addr - Address of the voter
+// Keyed by poll first so all ballots of a poll can be ranged over.
+pub const BALLOTS: Map<(String, Addr), Ballot> = Map::new("ballots");
+
 // String - Poll UUID this vote is for.
-pub const BALLOTS: Map<(Addr, String), Ballot> = Map::new("ballots");
+// String - cw721 token id that voted, ballots of NFT gated polls stay with the token
+// when it changes hands.
+pub const TOKEN_BALLOTS: Map<(String, String), Ballot> = Map::new("token_ballots");
+
+// Reward for voting attached to a poll by its creator
+#[cw_serde]
+pub struct Reward {
+    pub amount: Coin,
+    // Share by ballot weight instead of one share per ballot
+    pub by_weight: bool,
+    // Seconds after the poll closes ballots can claim their share
+    pub claim_period: u64,
+    // Shares are counted as ballots are cast, the deadline is set when the poll closes
+    pub total_shares: Uint128,
+    pub claim_deadline: Option<Timestamp>,
+    pub claimed: Uint128,
+}
+
+// The key is the poll id, the reward is removed once the remainder is reclaimed
+pub const REWARDS: Map<String, Reward> = Map::new("rewards");
+
+// String - Poll id
+// Addr - Voter that claimed their share
+pub const REWARD_CLAIMS: Map<(String, Addr), Empty> = Map::new("reward_claims");
+
+// Grants pool conviction voting proposals are paid from
+#[cw_serde]
+pub struct ConvictionPool {
+    pub denom: String,
+    // Funds available for grants, stakes are held separately
+    pub funds: Uint128,
+    // Tokens staked on open proposals
+    pub total_staked: Uint128,
+    // Share of conviction kept every block
+    pub decay: Decimal,
+    // Share of the funds a proposal must request less than
+    pub max_ratio: Decimal,
+    // Scales the threshold every proposal has to reach, above 0
+    pub weight: Decimal,
+    // Share of the funds the threshold counts as staked when less is staked
+    pub min_threshold_stake: Decimal,
+}
+
+pub const CONVICTION_POOL: Item<ConvictionPool> = Item::new("conviction_pool");
+
+#[cw_serde]
+pub struct ConvictionProposal {
+    pub creator: Addr,
+    pub title: String,
+    pub beneficiary: Addr,
+    pub requested: Uint128,
+    pub staked: Uint128,
+    // Conviction as of block height `updated_at`
+    pub conviction: Uint128,
+    pub updated_at: u64,
+    // Open until the grant is paid out, then Executed
+    pub status: PollStatus,
+}
+
+// The key is the proposal id, kept apart from POLLS
+pub const CONVICTION_PROPOSALS: Map<String, ConvictionProposal> = Map::new("conviction_proposals");
+
+// Quadratic funding round run on a poll, the poll's options are the project addresses
+#[cw_serde]
+pub struct FundingRound {
+    pub denom: String,
+    // Deposited by the admin when the round is created, shared out on Distribute
+    pub matching_pool: Uint128,
+}
+
+// The key is the poll id
+pub const FUNDING_ROUNDS: Map<String, FundingRound> = Map::new("funding_rounds");
+
+// String - Poll id
+// String - Project
+// Addr - Contributor, the value is the total contributed
+pub const CONTRIBUTIONS: Map<(String, String, Addr), Uint128> = Map::new("contributions");
+
+// String - Conviction proposal id
+// Addr - Staker, the value is the amount staked
+pub const CONVICTION_STAKES: Map<(String, Addr), Uint128> = Map::new("conviction_stakes");
@@ -0,0 +1,139 @@
+use cosmwasm_std::{Decimal256, Uint128, Uint256};
+
+use crate::msg::StvRound;
+
+// Fixed point scale for ballot values, so surplus transfers keep fractions of a vote
+const SCALE: u128 = 1_000_000_000;
+
+// A ranked ballot as indexes into the candidates, with its weight
+pub struct RankedBallot {
+    pub ranking: Vec<usize>,
+    pub weight: Uint128,
+}
+
+pub struct StvResult {
+    pub elected: Vec<String>,
+    pub rounds: Vec<StvRound>,
+}
+
+// Single Transferable Vote with the Droop quota.
+// Every round either elects the candidate with the most votes once it reaches the quota,
+// transferring its surplus at a reduced value (Gregory method), or eliminates the candidate
+// with the fewest votes and transfers its ballots at their current value.
+// Ties are broken by the order the candidates were created in: the earlier candidate is
+// elected first and the later one is eliminated first.
+pub fn stv(candidates: &[String], ballots: &[RankedBallot], seats: usize) -> StvResult {
+    let scale = Uint256::from(SCALE);
+    let total = ballots.iter().fold(Uint256::zero(), |total, ballot| {
+        total + Uint256::from(ballot.weight)
+    });
+    let quota = (total / Uint256::from(seats as u128 + 1) + Uint256::one()) * scale;
+
+    // Current value of each ballot and the position of its current preference
+    let mut values: Vec<Uint256> = ballots
+        .iter()
+        .map(|ballot| Uint256::from(ballot.weight) * scale)
+        .collect();
+    let mut positions: Vec<usize> = vec![0; ballots.len()];
+    let mut continuing: Vec<bool> = vec![true; candidates.len()];
+    let mut elected: Vec<String> = vec![];
+    let mut rounds: Vec<StvRound> = vec![];
+
+    while elected.len() < seats {
+        // Move every ballot to its highest continuing preference and count
+        let mut counts: Vec<Uint256> = vec![Uint256::zero(); candidates.len()];
+        for (i, ballot) in ballots.iter().enumerate() {
+            while positions[i] < ballot.ranking.len() && !continuing[ballot.ranking[positions[i]]] {
+                positions[i] += 1;
+            }
+            if let Some(candidate) = ballot.ranking.get(positions[i]) {
+                counts[*candidate] += values[i];
+            }
+        }
+
+        let remaining: Vec<usize> = (0..candidates.len()).filter(|c| continuing[*c]).collect();
+        let mut round = StvRound {
+            counts: remaining
+                .iter()
+                .map(|c| {
+                    (
+                        candidates[*c].clone(),
+                        Decimal256::from_ratio(counts[*c], scale),
+                    )
+                })
+                .collect(),
+            elected: vec![],
+            eliminated: None,
+        };
+
+        // Fewer candidates than seats left, all of them are elected
+        if remaining.len() <= seats - elected.len() {
+            for c in remaining {
+                round.elected.push(candidates[c].clone());
+                elected.push(candidates[c].clone());
+            }
+            rounds.push(round);
+            break;
+        }
+
+        // max_by_key returns the last maximum, iterate in reverse so the earliest candidate wins
+        let leader = *remaining.iter().rev().max_by_key(|c| counts[**c]).unwrap();
+        if counts[leader] >= quota {
+            let surplus = counts[leader] - quota;
+            for (i, ballot) in ballots.iter().enumerate() {
+                if ballot.ranking.get(positions[i]) == Some(&leader) {
+                    values[i] = values[i].multiply_ratio(surplus, counts[leader]);
+                }
+            }
+            continuing[leader] = false;
+            round.elected.push(candidates[leader].clone());
+            elected.push(candidates[leader].clone());
+        } else {
+            // min_by_key returns the first minimum, iterate in reverse to eliminate the latest
+            let loser = *remaining.iter().rev().min_by_key(|c| counts[**c]).unwrap();
+            continuing[loser] = false;
+            round.eliminated = Some(candidates[loser].clone());
+        }
+        rounds.push(round);
+    }
+
+    StvResult { elected, rounds }
+}
+
+// The candidate preferred over every other candidate by a majority of the head to head ballots.
+// matrix[i][j] is the weight of ballots ranking candidate i above candidate j.
+pub fn condorcet_winner(matrix: &[Vec<Uint128>]) -> Option<usize> {
+    (0..matrix.len()).find(|i| (0..matrix.len()).all(|j| j == *i || matrix[*i][j] > matrix[j][*i]))
+}
+
+// Schulze method, candidates whose strongest beatpath to every other candidate is at least as
+// strong as the reverse path. This is the Condorcet winner alone when there is one, ties between
+// candidates are all returned in candidate order.
+pub fn schulze(matrix: &[Vec<Uint128>]) -> Vec<usize> {
+    let n = matrix.len();
+    // Strength of the strongest path, starting from the pairwise wins
+    let mut paths: Vec<Vec<Uint128>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    if matrix[i][j] > matrix[j][i] {
+                        matrix[i][j]
+                    } else {
+                        Uint128::zero()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    for k in 0..n {
+        for i in (0..n).filter(|i| *i != k) {
+            for j in (0..n).filter(|j| *j != k && *j != i) {
+                paths[i][j] = paths[i][j].max(paths[i][k].min(paths[k][j]));
+            }
+        }
+    }
+
+    (0..n)
+        .filter(|i| (0..n).all(|j| j == *i || paths[*i][j] >= paths[j][*i]))
+        .collect()
+}
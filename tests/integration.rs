@@ -0,0 +1,638 @@
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_std::{
+    coin, coins, Addr, Api, Binary, BlockInfo, Coin, CustomQuery, Decimal, Empty, Event, GovMsg,
+    Querier, Storage, Uint128,
+};
+use cw_multi_test::{
+    next_block, App, AppBuilder, AppResponse, Contract, ContractWrapper, CosmosRouter, Executor,
+    Gov, Module,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+
+use cw_starter::helpers::PollContract;
+use cw_starter::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg, VoteMsg};
+use cw_starter::state::{Ballot, PollStatus, VotingMode};
+use cw_starter::ContractError;
+
+const ADMIN: &str = "admin";
+const USER1: &str = "user1";
+const USER2: &str = "user2";
+const USER3: &str = "user3";
+
+fn poll_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw_starter::contract::execute,
+        cw_starter::contract::instantiate,
+        cw_starter::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Store the code and instantiate it with ADMIN as the admin
+fn setup() -> (App, PollContract) {
+    let mut app = App::default();
+    let code_id = app.store_code(poll_contract());
+    let addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &InstantiateMsg { admin: None },
+            &[],
+            "cw-starter",
+            None,
+        )
+        .unwrap();
+    (app, PollContract(addr))
+}
+
+// Like setup, with the given ujuno balances
+fn setup_with_balances(balances: &[(&str, u128)]) -> (App, PollContract) {
+    let mut app = AppBuilder::new().build(|router, _, storage| {
+        for (address, amount) in balances {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(*address), coins(*amount, "ujuno"))
+                .unwrap();
+        }
+    });
+    let code_id = app.store_code(poll_contract());
+    let addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &InstantiateMsg { admin: None },
+            &[],
+            "cw-starter",
+            None,
+        )
+        .unwrap();
+    (app, PollContract(addr))
+}
+
+fn balance(app: &App, address: impl Into<String>) -> Coin {
+    app.wrap().query_balance(address, "ujuno").unwrap()
+}
+
+fn create_poll(app: &mut App, contract: &PollContract, sender: &str, poll_id: &str) {
+    let msg = contract
+        .create_poll(CreatePollMsg {
+            poll_id: poll_id.to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+    app.execute(Addr::unchecked(sender), msg).unwrap();
+}
+
+fn vote(app: &mut App, contract: &PollContract, sender: &str, poll_id: &str, option: &str) {
+    let msg = contract
+        .vote(VoteMsg {
+            poll_id: poll_id.to_string(),
+            vote: option.to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    app.execute(Addr::unchecked(sender), msg).unwrap();
+}
+
+fn tally(app: &App, contract: &PollContract, poll_id: &str) -> Vec<(String, Uint128)> {
+    contract
+        .poll(&app.wrap(), poll_id)
+        .unwrap()
+        .poll
+        .unwrap()
+        .options
+}
+
+#[test]
+fn instantiate_sets_admin() {
+    let (app, contract) = setup();
+
+    let config = contract.config(&app.wrap()).unwrap();
+    assert_eq!(config.admin, Addr::unchecked(ADMIN));
+    assert!(contract.all_polls(&app.wrap()).unwrap().polls.is_empty());
+}
+
+#[test]
+fn create_vote_and_change_vote_across_blocks() {
+    let (mut app, contract) = setup();
+    create_poll(&mut app, &contract, USER1, "poll_1");
+
+    // Every user votes in a different block
+    vote(&mut app, &contract, USER1, "poll_1", "Juno");
+    app.update_block(next_block);
+    vote(&mut app, &contract, USER2, "poll_1", "Juno");
+    app.update_block(next_block);
+    vote(&mut app, &contract, USER3, "poll_1", "Osmosis");
+
+    assert_eq!(
+        tally(&app, &contract, "poll_1"),
+        vec![
+            ("Cosmos Hub".to_string(), Uint128::new(0)),
+            ("Juno".to_string(), Uint128::new(2)),
+            ("Osmosis".to_string(), Uint128::new(1))
+        ]
+    );
+
+    // USER2 changes their mind a few blocks later
+    app.update_block(|block| block.height += 10);
+    vote(&mut app, &contract, USER2, "poll_1", "Cosmos Hub");
+
+    assert_eq!(
+        tally(&app, &contract, "poll_1"),
+        vec![
+            ("Cosmos Hub".to_string(), Uint128::new(1)),
+            ("Juno".to_string(), Uint128::new(1)),
+            ("Osmosis".to_string(), Uint128::new(1))
+        ]
+    );
+    let ballot = contract.ballot(&app.wrap(), "poll_1", USER2).unwrap();
+    assert_eq!(
+        ballot.vote,
+        Some(Ballot {
+            option: "Cosmos Hub".to_string(),
+            weight: Uint128::one(),
+            delegators: vec![],
+            allocation: vec![],
+            ranking: vec![],
+        })
+    );
+}
+
+#[test]
+fn vote_on_unknown_poll_or_option_fails() {
+    let (mut app, contract) = setup();
+
+    let msg = contract
+        .vote(VoteMsg {
+            poll_id: "poll_1".to_string(),
+            vote: "Juno".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let err = app.execute(Addr::unchecked(USER1), msg).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PollNotFound {}
+    );
+
+    create_poll(&mut app, &contract, USER1, "poll_1");
+    let msg = contract
+        .vote(VoteMsg {
+            poll_id: "poll_1".to_string(),
+            vote: "DVPN".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let err = app.execute(Addr::unchecked(USER1), msg).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OptionNonExistent {}
+    );
+}
+
+#[test]
+fn revoke_ballot() {
+    let (mut app, contract) = setup();
+    create_poll(&mut app, &contract, USER1, "poll_1");
+    vote(&mut app, &contract, USER1, "poll_1", "Juno");
+    vote(&mut app, &contract, USER2, "poll_1", "Juno");
+    app.update_block(next_block);
+
+    // USER3 cannot revoke someone else's ballot
+    let msg = ExecuteMsg::Revoke {
+        sender: Addr::unchecked(USER1),
+        poll_id: "poll_1".to_string(),
+    };
+    let err = app
+        .execute(Addr::unchecked(USER3), contract.call(msg.clone()).unwrap())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // USER1 revokes their own ballot, the admin revokes USER2's
+    app.execute(Addr::unchecked(USER1), contract.call(msg).unwrap())
+        .unwrap();
+    let msg = ExecuteMsg::Revoke {
+        sender: Addr::unchecked(USER2),
+        poll_id: "poll_1".to_string(),
+    };
+    app.execute(Addr::unchecked(ADMIN), contract.call(msg).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        tally(&app, &contract, "poll_1")[1],
+        ("Juno".to_string(), Uint128::new(0))
+    );
+    let ballot = contract.ballot(&app.wrap(), "poll_1", USER1).unwrap();
+    assert_eq!(ballot.vote, None);
+
+    // A revoked voter can vote again
+    vote(&mut app, &contract, USER1, "poll_1", "Osmosis");
+    assert_eq!(
+        tally(&app, &contract, "poll_1")[2],
+        ("Osmosis".to_string(), Uint128::new(1))
+    );
+}
+
+#[test]
+fn delete_poll_and_recreate() {
+    let (mut app, contract) = setup();
+    create_poll(&mut app, &contract, USER1, "poll_1");
+    create_poll(&mut app, &contract, USER2, "poll_2");
+    vote(&mut app, &contract, USER1, "poll_1", "Juno");
+    vote(&mut app, &contract, USER2, "poll_1", "Osmosis");
+
+    // Only the creator or the admin can delete
+    let err = app
+        .execute(
+            Addr::unchecked(USER2),
+            contract.delete_poll("poll_1").unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute(
+        Addr::unchecked(USER1),
+        contract.delete_poll("poll_1").unwrap(),
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(ADMIN),
+        contract.delete_poll("poll_2").unwrap(),
+    )
+    .unwrap();
+
+    assert!(contract.all_polls(&app.wrap()).unwrap().polls.is_empty());
+    assert!(contract.poll(&app.wrap(), "poll_1").unwrap().poll.is_none());
+
+    // Recreating the poll id starts from a clean slate
+    app.update_block(next_block);
+    create_poll(&mut app, &contract, USER3, "poll_1");
+    assert!(contract
+        .ballot(&app.wrap(), "poll_1", USER1)
+        .unwrap()
+        .vote
+        .is_none());
+    vote(&mut app, &contract, USER1, "poll_1", "Cosmos Hub");
+    assert_eq!(
+        tally(&app, &contract, "poll_1"),
+        vec![
+            ("Cosmos Hub".to_string(), Uint128::new(1)),
+            ("Juno".to_string(), Uint128::new(0)),
+            ("Osmosis".to_string(), Uint128::new(0))
+        ]
+    );
+}
+
+// Gov module accepting every vote, reported as a "gov_vote" event
+struct StubGov;
+
+impl Module for StubGov {
+    type ExecT = GovMsg;
+    type QueryT = Empty;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        sender: Addr,
+        msg: GovMsg,
+    ) -> anyhow::Result<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let event = match msg {
+            GovMsg::Vote { proposal_id, vote } => Event::new("gov_vote")
+                .add_attribute("voter", sender)
+                .add_attribute("proposal_id", proposal_id.to_string())
+                .add_attribute("option", format!("{:?}", vote)),
+        };
+        Ok(AppResponse {
+            events: vec![event],
+            data: None,
+        })
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _msg: Empty,
+    ) -> anyhow::Result<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        anyhow::bail!("unsupported")
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        _request: Empty,
+    ) -> anyhow::Result<Binary> {
+        anyhow::bail!("unsupported")
+    }
+}
+
+impl Gov for StubGov {}
+
+#[test]
+fn cast_gov_vote_on_linked_proposal() {
+    let mut app = AppBuilder::new().with_gov(StubGov).build(|_, _, _| ());
+    let code_id = app.store_code(poll_contract());
+    let addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &InstantiateMsg { admin: None },
+            &[],
+            "cw-starter",
+            None,
+        )
+        .unwrap();
+    let contract = PollContract(addr);
+
+    let msg = contract
+        .create_poll(CreatePollMsg {
+            poll_id: "prop_7".to_string(),
+            question: "How should we vote on proposal 7?".to_string(),
+            mode: VotingMode::Proposal {
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(50),
+                veto_threshold: Decimal::permille(334),
+                electorate: Uint128::new(3),
+            },
+            gov_proposal_id: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+    app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+    for (sender, option) in [(USER1, "yes"), (USER2, "yes"), (USER3, "no")] {
+        let msg = contract
+            .vote(VoteMsg {
+                poll_id: "prop_7".to_string(),
+                vote: option.to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        app.execute(Addr::unchecked(sender), msg).unwrap();
+    }
+
+    let msg = contract.cast_gov_vote("prop_7").unwrap();
+    let err = app.execute(Addr::unchecked(USER1), msg).unwrap_err();
+    assert_eq!(
+        ContractError::PollNotClosed {},
+        err.downcast::<ContractError>().unwrap()
+    );
+
+    app.update_block(next_block);
+    let msg = contract.close_poll("prop_7").unwrap();
+    app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+    let msg = contract.cast_gov_vote("prop_7").unwrap();
+    let res = app.execute(Addr::unchecked(USER1), msg).unwrap();
+
+    // The contract itself votes on the chain proposal
+    let vote = res.events.iter().find(|e| e.ty == "gov_vote").unwrap();
+    let attr = |key: &str| {
+        vote.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.clone())
+    };
+    assert_eq!(attr("voter"), Some(contract.addr().to_string()));
+    assert_eq!(attr("proposal_id"), Some("7".to_string()));
+    assert_eq!(attr("option"), Some("Yes".to_string()));
+
+    let poll = contract.poll(&app.wrap(), "prop_7").unwrap().poll.unwrap();
+    assert_eq!(poll.status, PollStatus::Executed);
+}
+
+#[test]
+fn lock_and_unlock_funds() {
+    let (mut app, contract) = setup_with_balances(&[(USER1, 1000)]);
+
+    app.execute_contract(
+        Addr::unchecked(USER1),
+        contract.addr(),
+        &ExecuteMsg::Lock { duration: 100 },
+        &coins(600, "ujuno"),
+    )
+    .unwrap();
+    assert_eq!(balance(&app, USER1), coin(400, "ujuno"));
+    assert_eq!(balance(&app, contract.addr()), coin(600, "ujuno"));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            contract.addr(),
+            &ExecuteMsg::Unlock {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::LockNotExpired {},
+        err.downcast::<ContractError>().unwrap()
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    app.execute_contract(
+        Addr::unchecked(USER1),
+        contract.addr(),
+        &ExecuteMsg::Unlock {},
+        &[],
+    )
+    .unwrap();
+    assert_eq!(balance(&app, USER1), coin(1000, "ujuno"));
+    assert_eq!(balance(&app, contract.addr()), coin(0, "ujuno"));
+}
+
+#[test]
+fn fund_and_distribute_funding_round() {
+    let (mut app, contract) =
+        setup_with_balances(&[(ADMIN, 1000), (USER1, 4), (USER2, 9), (USER3, 25)]);
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        contract.addr(),
+        &ExecuteMsg::CreateFundingRound(CreatePollMsg {
+            poll_id: "round_1".to_string(),
+            question: "Which public goods should we fund?".to_string(),
+            options: vec!["project1".to_string(), "project2".to_string()],
+            ..Default::default()
+        }),
+        &coins(1000, "ujuno"),
+    )
+    .unwrap();
+    for (sender, project, amount) in [
+        (USER1, "project1", 4),
+        (USER2, "project1", 9),
+        (USER3, "project2", 25),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            contract.addr(),
+            &ExecuteMsg::Contribute {
+                poll_id: "round_1".to_string(),
+                project: project.to_string(),
+            },
+            &coins(amount, "ujuno"),
+        )
+        .unwrap();
+    }
+    assert_eq!(balance(&app, contract.addr()), coin(1038, "ujuno"));
+
+    let msg = contract.close_poll("round_1").unwrap();
+    app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+    app.execute_contract(
+        Addr::unchecked(USER1),
+        contract.addr(),
+        &ExecuteMsg::Distribute {
+            poll_id: "round_1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // project1 is matched (2 + 3)^2 - 13 = 12, a single contributor is not matched,
+    // and the rest of the matching pool goes back to the admin
+    assert_eq!(balance(&app, "project1"), coin(25, "ujuno"));
+    assert_eq!(balance(&app, "project2"), coin(25, "ujuno"));
+    assert_eq!(balance(&app, ADMIN), coin(988, "ujuno"));
+    assert_eq!(balance(&app, contract.addr()), coin(0, "ujuno"));
+}
+
+#[test]
+fn stake_and_execute_conviction_proposal() {
+    let (mut app, contract) = setup_with_balances(&[(USER1, 600), (USER2, 400), (USER3, 10000)]);
+    let execute = |app: &mut App, sender: &str, msg: ExecuteMsg, funds: &[Coin]| {
+        app.execute_contract(Addr::unchecked(sender), contract.addr(), &msg, funds)
+    };
+
+    let msg = ExecuteMsg::SetConvictionPool {
+        denom: "ujuno".to_string(),
+        decay: Decimal::percent(90),
+        max_ratio: Decimal::percent(20),
+        weight: Decimal::from_ratio(25u64, 10000u64),
+        min_threshold_stake: Decimal::percent(5),
+    };
+    execute(&mut app, ADMIN, msg, &[]).unwrap();
+    let msg = ExecuteMsg::FundConvictionPool {};
+    execute(&mut app, USER3, msg, &coins(10000, "ujuno")).unwrap();
+    let msg = ExecuteMsg::CreateConvictionProposal {
+        proposal_id: "grant".to_string(),
+        title: "Fund the block explorer".to_string(),
+        beneficiary: "grantee".to_string(),
+        requested: 1000u128.into(),
+    };
+    execute(&mut app, USER3, msg, &[]).unwrap();
+
+    let stake = ExecuteMsg::StakeConviction {
+        proposal_id: "grant".to_string(),
+    };
+    execute(&mut app, USER1, stake.clone(), &coins(600, "ujuno")).unwrap();
+    app.update_block(next_block);
+    execute(&mut app, USER2, stake, &coins(400, "ujuno")).unwrap();
+    assert_eq!(balance(&app, USER1), coin(0, "ujuno"));
+    assert_eq!(balance(&app, contract.addr()), coin(11000, "ujuno"));
+
+    // Conviction reaches the threshold of 2500 three blocks after the second stake
+    let execute_grant = ExecuteMsg::ExecuteConviction {
+        proposal_id: "grant".to_string(),
+    };
+    app.update_block(|block| block.height += 2);
+    let err = execute(&mut app, USER3, execute_grant.clone(), &[]).unwrap_err();
+    assert_eq!(
+        ContractError::ConvictionBelowThreshold {},
+        err.downcast::<ContractError>().unwrap()
+    );
+    app.update_block(next_block);
+    execute(&mut app, USER3, execute_grant, &[]).unwrap();
+    assert_eq!(balance(&app, "grantee"), coin(1000, "ujuno"));
+
+    // Stakes are returned, the pool keeps the rest of its funds
+    for (sender, amount) in [(USER1, 600u128), (USER2, 400)] {
+        let msg = ExecuteMsg::UnstakeConviction {
+            proposal_id: "grant".to_string(),
+            amount: amount.into(),
+        };
+        execute(&mut app, sender, msg, &[]).unwrap();
+        assert_eq!(balance(&app, sender), coin(amount, "ujuno"));
+    }
+    assert_eq!(balance(&app, contract.addr()), coin(9000, "ujuno"));
+}
+
+#[test]
+fn claim_and_reclaim_poll_reward() {
+    let (mut app, contract) = setup_with_balances(&[(ADMIN, 900)]);
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        contract.addr(),
+        &ExecuteMsg::CreatePoll(CreatePollMsg {
+            poll_id: "poll_1".to_string(),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec!["Cosmos Hub".to_string(), "Juno".to_string()],
+            reward_claim_period: Some(100),
+            ..Default::default()
+        }),
+        &coins(900, "ujuno"),
+    )
+    .unwrap();
+    for voter in [USER1, USER2, USER3] {
+        vote(&mut app, &contract, voter, "poll_1", "Juno");
+    }
+    let msg = contract.close_poll("poll_1").unwrap();
+    app.execute(Addr::unchecked(ADMIN), msg).unwrap();
+
+    // Every ballot gets a third, USER3 does not claim theirs in time
+    let claim = ExecuteMsg::ClaimReward {
+        poll_id: "poll_1".to_string(),
+    };
+    for voter in [USER1, USER2] {
+        app.execute_contract(Addr::unchecked(voter), contract.addr(), &claim, &[])
+            .unwrap();
+        assert_eq!(balance(&app, voter), coin(300, "ujuno"));
+    }
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    let err = app
+        .execute_contract(Addr::unchecked(USER3), contract.addr(), &claim, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ClaimPeriodOver {},
+        err.downcast::<ContractError>().unwrap()
+    );
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        contract.addr(),
+        &ExecuteMsg::ReclaimReward {
+            poll_id: "poll_1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(balance(&app, ADMIN), coin(300, "ujuno"));
+    assert_eq!(balance(&app, USER3), coin(0, "ujuno"));
+    assert_eq!(balance(&app, contract.addr()), coin(0, "ujuno"));
+}